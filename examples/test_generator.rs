@@ -1,6 +1,6 @@
 use crates_io_og_image::{
     OgImageAuthorData, OgImageCommunityData, OgImageData, OgImageDataPoint, OgImageGenerator,
-    OgImageGraphData,
+    OgImageGraphData, OgImageMarketKind,
 };
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::{EnvFilter, fmt};
@@ -31,6 +31,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "https://avatars.githubusercontent.com/u/141300",
         ),
         outcome: "NONE",
+        kind: OgImageMarketKind::Probability,
         graph: &[
             OgImageGraphData {
                 outcome: "No",
@@ -38,35 +39,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 data: &[
                     OgImageDataPoint {
                         time: 1744249342,
-                        value: 50,
+                        value: 50.,
                     },
                     OgImageDataPoint {
                         time: 1744249396,
-                        value: 40,
+                        value: 40.,
                     },
                     OgImageDataPoint {
                         time: 1744352237,
-                        value: 25,
+                        value: 25.,
                     },
                     OgImageDataPoint {
                         time: 1744757651,
-                        value: 99,
+                        value: 99.,
                     },
                     OgImageDataPoint {
                         time: 1744924914,
-                        value: 95,
+                        value: 95.,
                     },
                     OgImageDataPoint {
                         time: 1745010415,
-                        value: 60,
+                        value: 60.,
                     },
                     OgImageDataPoint {
                         time: 1745266299,
-                        value: 90,
+                        value: 90.,
                     },
                     OgImageDataPoint {
                         time: 1745466299,
-                        value: 90,
+                        value: 90.,
                     },
                 ],
             },
@@ -76,44 +77,46 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 data: &[
                     OgImageDataPoint {
                         time: 1744249342,
-                        value: 50,
+                        value: 50.,
                     },
                     OgImageDataPoint {
                         time: 1744249423,
-                        value: 60,
+                        value: 60.,
                     },
                     OgImageDataPoint {
                         time: 1744924887,
-                        value: 5,
+                        value: 5.,
                     },
                     OgImageDataPoint {
                         time: 1745010399,
-                        value: 40,
+                        value: 40.,
                     },
                     OgImageDataPoint {
                         time: 1745010432,
-                        value: 50,
+                        value: 50.,
                     },
                     OgImageDataPoint {
                         time: 1745010443,
-                        value: 50,
+                        value: 50.,
                     },
                     OgImageDataPoint {
                         time: 1745266284,
-                        value: 10,
+                        value: 10.,
                     },
                     OgImageDataPoint {
                         time: 1745466299,
-                        value: 10,
+                        value: 10.,
                     },
                 ],
             },
         ],
+        closes_at: Some(1745700000),
+        resolved_at: None,
     };
     match generator.generate(data).await {
-        Ok(temp_file) => {
+        Ok(output) => {
             let output_path = "test_og_image.png";
-            std::fs::copy(temp_file.path(), output_path)?;
+            std::fs::copy(output.image.path(), output_path)?;
             println!("Successfully generated image at: {output_path}");
             println!(
                 "Image file size: {} bytes",
@@ -0,0 +1,443 @@
+//! Rendering many OpenGraph images from a JSON Lines stream.
+//!
+//! Every non-empty line of the input contains one [`BatchRecord`], i.e. the
+//! JSON form of [`OwnedOgImageData`] with an optional `id`. The records are
+//! rendered concurrently by a single [`OgImageGenerator`], and the result of
+//! every record is written as a [`BatchReportEntry`] to a JSON Lines report.
+//!
+//! Records are identified by their `id`, or by their line number if they
+//! have none, so an interrupted batch can be resumed by passing the
+//! successfully rendered records from the previous report to
+//! [`BatchOptions::with_completed`], see [`read_completed_records()`].
+//! Records without `id` are only skipped if the input is unchanged.
+
+use crate::{OgImageError, OgImageGenerator, OwnedOgImageData};
+use futures_util::{TryStreamExt, stream};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::pin::pin;
+use tokio::fs;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{debug, info, instrument, warn};
+
+/// Options for [`OgImageGenerator::render_batch()`].
+///
+/// # Examples
+///
+/// ```
+/// use crates_io_og_image::BatchOptions;
+///
+/// let options = BatchOptions::new("og-images").with_concurrency(8);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// Directory the rendered images are written to
+    pub output_dir: PathBuf,
+    /// Maximum number of images rendered at the same time
+    pub concurrency: usize,
+    /// Records that are skipped, e.g. from a previous report
+    pub completed: HashSet<BatchRecordId>,
+}
+
+impl BatchOptions {
+    /// Creates options writing images to the given directory, rendering as
+    /// many images at the same time as there are CPUs available.
+    pub fn new(output_dir: impl Into<PathBuf>) -> Self {
+        let concurrency = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            output_dir: output_dir.into(),
+            concurrency,
+            completed: HashSet::new(),
+        }
+    }
+
+    /// Sets the maximum number of images rendered at the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `concurrency` is zero.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        assert!(concurrency > 0, "batch concurrency must be at least 1");
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Sets the records that are skipped because they were already rendered.
+    pub fn with_completed(mut self, completed: HashSet<BatchRecordId>) -> Self {
+        self.completed = completed;
+        self
+    }
+}
+
+/// A single line of the batch input.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchRecord {
+    /// Optional identifier, used as the file name of the rendered image
+    /// instead of the cache key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The image data
+    #[serde(flatten)]
+    pub data: OwnedOgImageData,
+}
+
+/// Identity of a batch record, used to skip completed records when resuming.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BatchRecordId {
+    /// The `id` of the record
+    Id(String),
+    /// The line number of a record without `id`, starting at 1
+    Line(usize),
+}
+
+impl BatchRecordId {
+    fn new(id: Option<&str>, line: usize) -> Self {
+        match id {
+            Some(id) => Self::Id(id.to_string()),
+            None => Self::Line(line),
+        }
+    }
+}
+
+impl fmt::Display for BatchRecordId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "'{id}'"),
+            Self::Line(line) => write!(f, "line {line}"),
+        }
+    }
+}
+
+/// A single line of the batch report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchReportEntry {
+    /// Line number of the record in the input, starting at 1
+    pub line: usize,
+    /// Identifier of the record, if any
+    pub id: Option<String>,
+    /// Cache key of the record, unless the line could not be parsed
+    pub key: Option<String>,
+    /// Result of rendering the record
+    #[serde(flatten)]
+    pub status: BatchStatus,
+}
+
+/// Result of rendering a single batch record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchStatus {
+    /// The image was rendered and written to `output`
+    Success {
+        /// Path of the written image
+        output: PathBuf,
+        /// Whether the image was taken from the render cache
+        cache_hit: bool,
+    },
+    /// Rendering failed and the fallback image was written to `output`.
+    /// Degraded records are rendered again when resuming.
+    Degraded {
+        /// Path of the written fallback image
+        output: PathBuf,
+    },
+    /// The record could not be rendered
+    Failed {
+        /// Error message
+        error: String,
+    },
+}
+
+/// Number of records per result of [`OgImageGenerator::render_batch()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    /// Records that were rendered successfully
+    pub succeeded: usize,
+    /// Records for which the fallback image was written
+    pub degraded: usize,
+    /// Records that could not be rendered
+    pub failed: usize,
+    /// Records that were skipped because they were already completed
+    pub skipped: usize,
+}
+
+/// Reads the successfully rendered records from a report written by
+/// [`OgImageGenerator::render_batch()`].
+///
+/// Lines that cannot be parsed, e.g. a truncated last line of an interrupted
+/// batch, are ignored.
+pub async fn read_completed_records<R: AsyncBufRead + Unpin>(
+    report: R,
+) -> io::Result<HashSet<BatchRecordId>> {
+    let mut completed = HashSet::new();
+    let mut lines = report.lines();
+    while let Some(line) = lines.next_line().await? {
+        let Ok(entry) = serde_json::from_str::<BatchReportEntry>(&line) else {
+            continue;
+        };
+        if let BatchStatus::Success { .. } = entry.status {
+            completed.insert(BatchRecordId::new(entry.id.as_deref(), entry.line));
+        }
+    }
+
+    Ok(completed)
+}
+
+/// Returns `true` if the record identifier can safely be used as file name.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+impl OgImageGenerator {
+    /// Renders all records of a JSON Lines stream and writes a JSON Lines
+    /// report with one [`BatchReportEntry`] per record.
+    ///
+    /// Up to [`BatchOptions::concurrency`] records are rendered at the same
+    /// time, and report entries are written in the order the records finish.
+    /// Records that fail to parse or render, and records with the same `id`
+    /// as a previous record, are reported as [`BatchStatus::Failed`] without
+    /// aborting the batch; only I/O errors when reading the input or writing
+    /// the report abort it.
+    ///
+    /// Configure an [avatar cache](Self::with_avatar_cache) to download each
+    /// avatar only once per batch.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crates_io_og_image::{AvatarCache, BatchOptions, OgImageError, OgImageGenerator};
+    /// use tokio::io::BufReader;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), OgImageError> {
+    /// let generator = OgImageGenerator::from_environment()?
+    ///     .with_avatar_cache(AvatarCache::new(1000));
+    ///
+    /// let input = BufReader::new(tokio::fs::File::open("markets.jsonl").await?);
+    /// let report = tokio::fs::File::create("report.jsonl").await?;
+    /// let options = BatchOptions::new("og-images");
+    ///
+    /// let summary = generator.render_batch(input, report, &options).await?;
+    /// println!("Rendered {} images", summary.succeeded);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip_all, fields(output_dir = %options.output_dir.display()))]
+    pub async fn render_batch<R, W>(
+        &self,
+        input: R,
+        mut report: W,
+        options: &BatchOptions,
+    ) -> Result<BatchSummary, OgImageError>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        fs::create_dir_all(&options.output_dir).await?;
+
+        let lines = stream::try_unfold((input.lines(), 0), |(mut lines, number)| async move {
+            let line = lines.next_line().await?;
+            Ok::<_, io::Error>(line.map(|line| ((number + 1, line), (lines, number + 1))))
+        });
+
+        // Records are parsed in input order, so the first record with an `id`
+        // claims it, and later records with the same `id` fail
+        let mut ids = HashSet::new();
+        let entries = lines
+            .try_filter(|(_, line)| std::future::ready(!line.trim().is_empty()))
+            .map_ok(|(number, line)| {
+                let record = serde_json::from_str::<BatchRecord>(&line);
+                let duplicate = match &record {
+                    Ok(BatchRecord { id: Some(id), .. }) => !ids.insert(id.clone()),
+                    _ => false,
+                };
+                async move {
+                    let entry = self.render_batch_record(number, record, duplicate, options);
+                    Ok(entry.await)
+                }
+            })
+            .try_buffer_unordered(options.concurrency);
+
+        let mut entries = pin!(entries);
+        let mut summary = BatchSummary::default();
+        while let Some(entry) = entries.try_next().await? {
+            let Some(entry) = entry else {
+                summary.skipped += 1;
+                continue;
+            };
+
+            match &entry.status {
+                BatchStatus::Success { .. } => summary.succeeded += 1,
+                BatchStatus::Degraded { .. } => summary.degraded += 1,
+                BatchStatus::Failed { error } => {
+                    warn!(line = entry.line, error = %error, "Failed to render batch record");
+                    summary.failed += 1;
+                }
+            }
+
+            let mut json =
+                serde_json::to_vec(&entry).map_err(OgImageError::JsonSerializationError)?;
+            json.push(b'\n');
+            report.write_all(&json).await?;
+            report.flush().await?;
+        }
+
+        info!(
+            succeeded = summary.succeeded,
+            degraded = summary.degraded,
+            failed = summary.failed,
+            skipped = summary.skipped,
+            "Batch rendering finished"
+        );
+
+        Ok(summary)
+    }
+
+    /// Renders a single batch record, returning `None` if it was skipped.
+    ///
+    /// Records whose `id` was already used by a previous record fail, as
+    /// their image would overwrite the image of the previous record.
+    async fn render_batch_record(
+        &self,
+        line: usize,
+        record: serde_json::Result<BatchRecord>,
+        duplicate: bool,
+        options: &BatchOptions,
+    ) -> Option<BatchReportEntry> {
+        let failed = |id, key, error: String| BatchReportEntry {
+            line,
+            id,
+            key,
+            status: BatchStatus::Failed { error },
+        };
+
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => return Some(failed(None, None, format!("Invalid record: {err}"))),
+        };
+
+        let graph = record.data.graph();
+        let data = record.data.as_data(&graph);
+        let id = record.id;
+
+        if let Some(id) = id.as_ref().filter(|_| duplicate) {
+            let error = format!("Duplicate id '{id}' of a previous record");
+            return Some(failed(Some(id.clone()), None, error));
+        }
+
+        let record_id = BatchRecordId::new(id.as_deref(), line);
+        if options.completed.contains(&record_id) {
+            debug!(line, record = %record_id, "Skipping completed batch record");
+            return None;
+        }
+
+        let key = match self.cache_key(&data) {
+            Ok(key) => key,
+            Err(err) => return Some(failed(id, None, err.to_string())),
+        };
+
+        let name = match &id {
+            Some(id) if !is_valid_id(id) => {
+                let error = format!("Invalid id '{id}', expected letters, digits, '-', '_' or '.'");
+                return Some(failed(Some(id.clone()), Some(key), error));
+            }
+            Some(id) => id.as_str(),
+            None => key.as_str(),
+        };
+        let extension = self.format.extension();
+        let output_path = options.output_dir.join(format!("{name}.{extension}"));
+
+        let result = async {
+            let output = self.generate(data).await?;
+
+            // Copy the contents rather than the file, which is only readable by
+            // the current user
+            let image = fs::read(output.image.path()).await?;
+            fs::write(&output_path, image).await?;
+            Ok::<_, OgImageError>(output)
+        };
+
+        let status = match result.await {
+            Ok(output) if output.degraded => BatchStatus::Degraded {
+                output: output_path,
+            },
+            Ok(output) => BatchStatus::Success {
+                output: output_path,
+                cache_hit: output.cache_hit,
+            },
+            Err(err) => BatchStatus::Failed {
+                error: err.to_string(),
+            },
+        };
+
+        Some(BatchReportEntry {
+            line,
+            id,
+            key: Some(key),
+            status,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_entry_format() {
+        let entry = BatchReportEntry {
+            line: 3,
+            id: Some("market-1".to_string()),
+            key: Some("abc".to_string()),
+            status: BatchStatus::Success {
+                output: PathBuf::from("out/market-1.png"),
+                cache_hit: false,
+            },
+        };
+
+        let json = serde_json::to_string(&entry).unwrap();
+        assert_eq!(
+            json,
+            r#"{"line":3,"id":"market-1","key":"abc","status":"success","output":"out/market-1.png","cache_hit":false}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<BatchReportEntry>(&json).unwrap(),
+            entry
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_completed_records() {
+        let report = [
+            r#"{"line":1,"id":null,"key":"a","status":"success","output":"a.png","cache_hit":false}"#,
+            r#"{"line":2,"id":"market-1","key":"b","status":"success","output":"market-1.png","cache_hit":true}"#,
+            r#"{"line":3,"id":null,"key":"b","status":"failed","error":"boom"}"#,
+            r#"{"line":4,"id":null,"key":"c","status":"degraded","output":"c.png"}"#,
+            r#"{"line":5,"id":null,"key":null,"status":"failed","error":"Invalid record"}"#,
+            r#"{"line":6,"id":null,"key":"e","status":"succ"#,
+        ];
+        let report = report.join("\n");
+
+        let completed = read_completed_records(report.as_bytes()).await.unwrap();
+        let expected = [
+            BatchRecordId::Line(1),
+            BatchRecordId::Id("market-1".to_string()),
+        ];
+        assert_eq!(completed, HashSet::from(expected));
+    }
+
+    #[test]
+    fn test_valid_ids() {
+        assert!(is_valid_id("market-1"));
+        assert!(is_valid_id("market_1.v2"));
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id(".."));
+        assert!(!is_valid_id("../market"));
+        assert!(!is_valid_id("markets/1"));
+    }
+}
@@ -0,0 +1,431 @@
+//! HTTP service for rendering OpenGraph images.
+//!
+//! The service exposes the following endpoints:
+//!
+//! - `POST /render` renders the JSON-encoded [`OwnedOgImageData`] in the
+//!   request body and responds with the PNG image. The `ETag` is derived from
+//!   the render cache key, so requests with a matching `If-None-Match` header
+//!   are answered with `304 Not Modified` without rendering the image.
+//! - `GET /health` responds with `200 OK` while the service is running.
+//!
+//! The generator is configured through the `TYPST_PATH`, `TYPST_FONT_PATH`,
+//! `OXIPNG_PATH` and `OG_IMAGE_TEMPLATE_DIR` environment variables, and the
+//! listen address through `OG_IMAGE_LISTEN_ADDRESS` (defaults to
+//! `127.0.0.1:3000`).
+
+use axum::Router;
+use axum::body::Body;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Json, State};
+use axum::http::header::{CACHE_CONTROL, CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use crates_io_og_image::{
+    DEFAULT_CACHE_CONTROL, OgImageError, OgImageGenerator, OwnedOgImageData, TypstDiagnostic,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::net::TcpListener;
+use tokio_util::io::ReaderStream;
+use tracing::level_filters::LevelFilter;
+use tracing::{error, info, warn};
+use tracing_subscriber::{EnvFilter, fmt};
+
+/// Address the service listens on if `OG_IMAGE_LISTEN_ADDRESS` is not set.
+const DEFAULT_LISTEN_ADDRESS: &str = "127.0.0.1:3000";
+
+/// `Cache-Control` header for degraded fallback images, which must not be cached.
+const NO_STORE: &str = "no-store";
+
+fn init_tracing() {
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+
+    fmt().compact().with_env_filter(env_filter).init();
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    init_tracing();
+
+    let generator = OgImageGenerator::from_environment()?;
+    let address = std::env::var("OG_IMAGE_LISTEN_ADDRESS");
+    let address = address.unwrap_or_else(|_| DEFAULT_LISTEN_ADDRESS.to_string());
+
+    let listener = TcpListener::bind(&address).await?;
+    info!(address = %listener.local_addr()?, "OpenGraph image service listening");
+
+    axum::serve(listener, router(generator))
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    info!("OpenGraph image service stopped");
+    Ok(())
+}
+
+/// Builds the router of the service, sharing one generator between all requests.
+fn router(generator: OgImageGenerator) -> Router {
+    Router::new()
+        .route("/render", post(render))
+        .route("/health", get(health))
+        .with_state(Arc::new(generator))
+}
+
+/// Resolves once the process receives `Ctrl+C` or `SIGTERM`.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            error!(error = %err, "Failed to listen for Ctrl+C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                error!(error = %err, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutdown signal received, finishing in-flight requests");
+}
+
+async fn health() -> &'static str {
+    "OK"
+}
+
+async fn render(
+    State(generator): State<Arc<OgImageGenerator>>,
+    headers: HeaderMap,
+    data: Result<Json<OwnedOgImageData>, JsonRejection>,
+) -> Result<Response, ApiError> {
+    let Json(data) = data?;
+    let graph = data.graph();
+    let data = data.as_data(&graph);
+
+    let etag = format!("\"{}\"", generator.cache_key(&data)?);
+    if if_none_match(&headers, &etag) {
+        let headers = [
+            (ETAG, etag),
+            (CACHE_CONTROL, DEFAULT_CACHE_CONTROL.to_string()),
+        ];
+        return Ok((StatusCode::NOT_MODIFIED, headers).into_response());
+    }
+
+    let output = generator.generate(data).await?;
+
+    // The temporary file is deleted when `output` is dropped, but the
+    // reopened handle keeps the contents readable while streaming
+    let file = File::from_std(output.image.reopen()?);
+    let body = Body::from_stream(ReaderStream::new(file));
+
+    let mut response = Response::new(body);
+    let headers = response.headers_mut();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(output.format.mime_type()),
+    );
+    headers.insert(CONTENT_LENGTH, HeaderValue::from(output.size));
+
+    match output.etag() {
+        Some(etag) if !output.degraded => {
+            let etag = HeaderValue::from_str(&etag).expect("ETags are hex-encoded hashes");
+            headers.insert(ETAG, etag);
+            let cache_control = HeaderValue::from_static(DEFAULT_CACHE_CONTROL);
+            headers.insert(CACHE_CONTROL, cache_control);
+        }
+        _ => {
+            warn!("Responding with degraded fallback image");
+            headers.insert(CACHE_CONTROL, HeaderValue::from_static(NO_STORE));
+        }
+    }
+
+    Ok(response)
+}
+
+/// Returns `true` if the `If-None-Match` header matches the given ETag.
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|value| value.trim())
+        .any(|value| value == "*" || value.trim_start_matches("W/") == etag)
+}
+
+/// Error response of the service, rendered as a JSON object.
+#[derive(Debug)]
+struct ApiError {
+    status: StatusCode,
+    body: ApiErrorBody,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiErrorBody {
+    error: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    diagnostics: Vec<TypstDiagnostic>,
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        let status = rejection.status();
+        let error = rejection.body_text();
+        let diagnostics = Vec::new();
+        Self {
+            status,
+            body: ApiErrorBody { error, diagnostics },
+        }
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(err: std::io::Error) -> Self {
+        OgImageError::IoError(err).into()
+    }
+}
+
+impl From<OgImageError> for ApiError {
+    fn from(err: OgImageError) -> Self {
+        let status = status_code(&err);
+        if status.is_server_error() {
+            error!(error = %err, "Failed to render OpenGraph image");
+        }
+
+        let error = err.to_string();
+        let diagnostics = match err {
+            OgImageError::TypstCompilationError { diagnostics, .. } => diagnostics,
+            _ => Vec::new(),
+        };
+
+        Self {
+            status,
+            body: ApiErrorBody { error, diagnostics },
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self.body)).into_response()
+    }
+}
+
+/// Maps an [`OgImageError`] to the HTTP status code of the error response.
+fn status_code(err: &OgImageError) -> StatusCode {
+    match err {
+        // The request data could not be rendered by the template
+        OgImageError::TypstCompilationError { .. }
+        | OgImageError::MissingTemplateInput { .. }
+        | OgImageError::InvalidInput(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        // An upstream service failed
+        OgImageError::AvatarDownloadError { .. }
+        | OgImageError::StoreRequestError { .. }
+        | OgImageError::StoreResponseError { .. } => StatusCode::BAD_GATEWAY,
+        // The Typst compilation took too long
+        OgImageError::TypstTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        // The service is misconfigured
+        OgImageError::TypstNotFound(_)
+        | OgImageError::EnvVarError(_)
+        | OgImageError::TemplateLoadError { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        OgImageError::AvatarWriteError { .. }
+        | OgImageError::JsonSerializationError(_)
+        | OgImageError::IoError(_)
+        | OgImageError::StoreIoError { .. }
+        | OgImageError::TempFileError(_)
+        | OgImageError::TempDirError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crates_io_og_image::{
+        FallbackImage, ImageFormat, MemoryRenderCache, OgImageGenerator, RenderCache,
+    };
+    use mockito::{Server, ServerGuard};
+    use std::path::PathBuf;
+
+    async fn create_mock_avatar_server() -> ServerGuard {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/test-avatar.png")
+            .with_status(200)
+            .with_header("content-type", "image/png")
+            .with_body(include_bytes!("../../template/assets/test-avatar.png"))
+            .create_async()
+            .await;
+
+        server
+    }
+
+    fn test_data(server_url: &str) -> OwnedOgImageData {
+        let avatar = format!("{server_url}/test-avatar.png");
+        serde_json::from_value(serde_json::json!({
+            "question": "Will the service respond?",
+            "author": { "name": "test-user", "avatar": avatar },
+            "community": { "handle": "test-community", "avatar": avatar },
+            "outcome": "NONE",
+        }))
+        .unwrap()
+    }
+
+    /// Starts the service on a random port and returns its base URL.
+    async fn spawn_service(generator: OgImageGenerator) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let service = axum::serve(listener, router(generator));
+        tokio::spawn(async move { service.await.unwrap() });
+        format!("http://{address}")
+    }
+
+    fn broken_generator() -> OgImageGenerator {
+        OgImageGenerator::default().with_typst_path(PathBuf::from("/nonexistent/typst"))
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let url = spawn_service(broken_generator()).await;
+
+        let response = reqwest::get(format!("{url}/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "OK");
+    }
+
+    #[tokio::test]
+    async fn test_render_cached_image() {
+        let avatar_server = create_mock_avatar_server().await;
+        let data = test_data(&avatar_server.url());
+
+        let graph = data.graph();
+        let cache_key = broken_generator().cache_key(data.as_data(&graph)).unwrap();
+        let render_cache = MemoryRenderCache::new(10);
+        let png = FallbackImage::Bundled.bytes();
+        render_cache
+            .put(&cache_key, png, ImageFormat::Png)
+            .await
+            .unwrap();
+
+        // Typst is not available, so the image can only come from the cache
+        let generator = broken_generator().with_render_cache(render_cache);
+        let url = spawn_service(generator).await;
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("{url}/render"))
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&data).unwrap())
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers[CONTENT_TYPE], "image/png");
+        assert_eq!(headers[CACHE_CONTROL], DEFAULT_CACHE_CONTROL);
+        assert_eq!(headers[ETAG], format!("\"{cache_key}\""));
+        assert_eq!(response.bytes().await.unwrap(), png);
+    }
+
+    #[tokio::test]
+    async fn test_render_not_modified() {
+        let data = test_data("https://example.com");
+        let graph = data.graph();
+        let cache_key = broken_generator().cache_key(data.as_data(&graph)).unwrap();
+
+        // Typst is not available, so rendering would fail if it was attempted
+        let url = spawn_service(broken_generator()).await;
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("{url}/render"))
+            .header(IF_NONE_MATCH, format!("\"other\", \"{cache_key}\""))
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&data).unwrap())
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[ETAG], format!("\"{cache_key}\""));
+    }
+
+    #[tokio::test]
+    async fn test_render_fallback_image() {
+        let avatar_server = create_mock_avatar_server().await;
+        let data = test_data(&avatar_server.url());
+
+        let generator = broken_generator().with_fallback_image(FallbackImage::Bundled);
+        let url = spawn_service(generator).await;
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("{url}/render"))
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&data).unwrap())
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CACHE_CONTROL], NO_STORE);
+        assert!(response.headers().get(ETAG).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_render_errors() {
+        let avatar_server = create_mock_avatar_server().await;
+        let data = test_data(&avatar_server.url());
+
+        let url = spawn_service(broken_generator()).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(format!("{url}/render"))
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&data).unwrap())
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = response.bytes().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let error = body["error"].as_str().unwrap();
+        assert!(error.starts_with("Failed to find or execute Typst binary"));
+
+        let response = client
+            .post(format!("{url}/render"))
+            .header(CONTENT_TYPE, "application/json")
+            .body(r#"{"question": "Missing fields?"}"#)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = response.bytes().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["error"].as_str().unwrap().contains("missing field"));
+    }
+}
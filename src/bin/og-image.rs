@@ -0,0 +1,557 @@
+//! Command-line tool for rendering OpenGraph images from JSON files.
+//!
+//! The input file contains the JSON form of [`OgImageData`](crates_io_og_image::OgImageData),
+//! see `template/data.json` for an example:
+//!
+//! ```bash
+//! og-image template/data.json --output market.png --scale 2
+//! cat template/data.json | og-image --format svg
+//! ```
+//!
+//! With `--batch`, the input is a JSON Lines file with one record per line,
+//! optionally with an `id` used as output file name. The images are written
+//! to the `--output` directory together with a `report.jsonl` file, and an
+//! interrupted batch can be continued with `--resume`:
+//!
+//! ```bash
+//! og-image --batch markets.jsonl --output og-images --jobs 8
+//! og-image --batch markets.jsonl --output og-images --resume
+//! ```
+//!
+//! The `TYPST_PATH`, `TYPST_FONT_PATH`, `OXIPNG_PATH` and
+//! `OG_IMAGE_TEMPLATE_DIR` environment variables are respected, and can be
+//! overridden with command-line flags.
+
+use clap::Parser;
+use crates_io_og_image::{
+    AvatarCache, BatchOptions, ChartOptions, Downsampling, ImageFormat, Layout, LineStyle, Locale,
+    Markers, OgImageError, OgImageGenerator, OptimizationConfig, OwnedOgImageData, Template, Theme,
+    TimeLabels, TimeScale, Tz, read_completed_records,
+};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use tokio::io::{AsyncBufRead, BufReader};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{EnvFilter, fmt};
+
+/// Number of avatars kept in memory in batch mode.
+const AVATAR_CACHE_SIZE: usize = 10_000;
+
+/// Renders an OpenGraph image from a JSON file.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// JSON file with the image data, or `-` to read from stdin
+    #[arg(default_value = "-")]
+    input: PathBuf,
+
+    /// Output file [default: the input file name, or `og-image`, with the format extension],
+    /// or the output directory in batch mode [default: `.`]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Render all records of a JSON Lines input
+    #[arg(long)]
+    batch: bool,
+
+    /// Maximum number of images rendered at the same time in batch mode
+    /// [default: the number of CPUs]
+    #[arg(short, long, requires = "batch", value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: Option<u16>,
+
+    /// Report file of batch mode [default: `report.jsonl` in the output directory]
+    #[arg(long, requires = "batch")]
+    report: Option<PathBuf>,
+
+    /// Skip records that were rendered successfully according to the report,
+    /// and append to the report instead of replacing it
+    #[arg(long, requires = "batch")]
+    resume: bool,
+
+    /// Output format (`png`, `svg` or `pdf`)
+    #[arg(short, long, default_value_t = ImageFormat::Png)]
+    format: ImageFormat,
+
+    /// Scale factor of PNG images, `1` results in 1200×630 pixels
+    #[arg(short, long, default_value_t = 1.0, value_parser = parse_scale)]
+    scale: f32,
+
+    /// Page layout (`opengraph` or `square`)
+    #[arg(short, long, default_value_t = Layout::OpenGraph)]
+    layout: Layout,
+
+    /// Color theme: `dark`, `light` or a JSON file with a custom theme
+    #[arg(long, value_parser = parse_theme)]
+    theme: Option<Theme>,
+
+    /// Time axis of the chart (`real`, `compressed:<max gap in seconds>` or `ordinal`)
+    #[arg(long, default_value_t = TimeScale::Real)]
+    time_scale: TimeScale,
+
+    /// Date labels below the chart (`none`, `start-end` or `start-middle-end`)
+    #[arg(long, default_value_t = TimeLabels::None)]
+    time_labels: TimeLabels,
+
+    /// Timezone of the date labels, e.g. `Europe/Berlin`
+    #[arg(long, default_value_t = Tz::UTC)]
+    timezone: Tz,
+
+    /// Locale of the date labels, e.g. `de_DE`
+    #[arg(long, default_value = "en_US", value_parser = parse_locale)]
+    locale: Locale,
+
+    /// Draw gridlines at 25%, 50% and 75% on the chart
+    #[arg(long)]
+    gridlines: bool,
+
+    /// Maximum number of data points per chart series, longer series are downsampled
+    #[arg(long, default_value_t = ChartOptions::default().max_points, value_parser = parse_max_points)]
+    max_points: usize,
+
+    /// Downsampling algorithm for long chart series (`lttb` or `min-max`)
+    #[arg(long, default_value_t = Downsampling::Lttb)]
+    downsampling: Downsampling,
+
+    /// Line style of the chart series (`straight` or `step`)
+    #[arg(long, default_value_t = LineStyle::Straight)]
+    line_style: LineStyle,
+
+    /// Data points of the chart series marked with a dot (`none`, `last` or `all`)
+    #[arg(long, default_value_t = Markers::All)]
+    markers: Markers,
+
+    /// Maximum number of outcomes drawn on the chart, the remaining ones are combined
+    #[arg(long, default_value_t = ChartOptions::default().max_outcomes, value_parser = parse_max_outcomes)]
+    max_outcomes: usize,
+
+    /// Number of decimal places of the probabilities on the chart, at most 4
+    #[arg(long, default_value_t = 0, value_parser = parse_precision)]
+    precision: u8,
+
+    /// Current time as a Unix timestamp, used for closing times like "Ends in 3d"
+    #[arg(long)]
+    now: Option<u64>,
+
+    /// Template directory with a `template.json` manifest, overrides `OG_IMAGE_TEMPLATE_DIR`
+    #[arg(short, long)]
+    template: Option<PathBuf>,
+
+    /// Path to the Typst binary, overrides `TYPST_PATH`
+    #[arg(long)]
+    typst_path: Option<PathBuf>,
+
+    /// Directory with the fonts used by Typst, overrides `TYPST_FONT_PATH`
+    #[arg(long)]
+    font_path: Option<PathBuf>,
+
+    /// Path to the oxipng binary, overrides `OXIPNG_PATH`
+    #[arg(long)]
+    oxipng_path: Option<PathBuf>,
+
+    /// Skip the PNG optimization step
+    #[arg(long)]
+    no_optimize: bool,
+}
+
+impl Args {
+    /// Returns the output path, derived from the input path if not specified.
+    fn output_path(&self) -> PathBuf {
+        if let Some(output) = &self.output {
+            return output.clone();
+        }
+
+        let stem = match self.input.file_stem() {
+            Some(stem) if self.input != Path::new("-") => stem,
+            _ => "og-image".as_ref(),
+        };
+
+        Path::new(stem).with_extension(self.format.extension())
+    }
+
+    /// Returns the output directory of batch mode.
+    fn output_dir(&self) -> PathBuf {
+        self.output.clone().unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Returns the report path of batch mode.
+    fn report_path(&self) -> PathBuf {
+        match &self.report {
+            Some(report) => report.clone(),
+            None => self.output_dir().join("report.jsonl"),
+        }
+    }
+
+    /// Returns the chart options of the command-line flags.
+    fn chart_options(&self) -> ChartOptions {
+        ChartOptions::default()
+            .with_time_scale(self.time_scale)
+            .with_time_labels(self.time_labels)
+            .with_timezone(self.timezone)
+            .with_locale(self.locale)
+            .with_gridlines(self.gridlines)
+            .with_max_points(self.max_points)
+            .with_downsampling(self.downsampling)
+            .with_line_style(self.line_style)
+            .with_markers(self.markers)
+            .with_max_outcomes(self.max_outcomes)
+            .with_precision(self.precision)
+    }
+
+    /// Creates the generator from the environment and the command-line flags.
+    fn generator(&self) -> Result<OgImageGenerator, OgImageError> {
+        let mut generator = OgImageGenerator::from_environment()?
+            .with_format(self.format)
+            .with_scale(self.scale)
+            .with_layout(self.layout)
+            .with_chart(self.chart_options());
+
+        if let Some(theme) = &self.theme {
+            generator = generator.with_theme(theme.clone());
+        }
+        if let Some(now) = self.now {
+            generator = generator.with_now(now);
+        }
+        if let Some(template) = &self.template {
+            generator = generator.with_template(Template::from_dir(template)?);
+        }
+        if let Some(typst_path) = &self.typst_path {
+            generator = generator.with_typst_path(typst_path.clone());
+        }
+        if let Some(font_path) = &self.font_path {
+            generator = generator.with_font_path(font_path.clone());
+        }
+        if let Some(oxipng_path) = &self.oxipng_path {
+            generator = generator.with_oxipng_path(oxipng_path.clone());
+        }
+        if self.no_optimize {
+            generator = generator.with_optimization(OptimizationConfig::off());
+        }
+        if self.batch {
+            generator = generator.with_avatar_cache(AvatarCache::new(AVATAR_CACHE_SIZE));
+        }
+
+        Ok(generator)
+    }
+
+    /// Reads the image data from the input file or stdin.
+    fn read_input(&self) -> Result<OwnedOgImageData, String> {
+        let mut json = String::new();
+        let result = if self.input == Path::new("-") {
+            std::io::stdin().read_to_string(&mut json)
+        } else {
+            std::fs::File::open(&self.input).and_then(|mut file| file.read_to_string(&mut json))
+        };
+
+        let input = self.input.display();
+        result.map_err(|err| format!("Failed to read {input}: {err}"))?;
+        serde_json::from_str(&json).map_err(|err| format!("Invalid image data in {input}: {err}"))
+    }
+}
+
+/// Opens the JSON Lines input file, or stdin, for batch mode.
+async fn open_batch_input(input: &Path) -> std::io::Result<Box<dyn AsyncBufRead + Unpin>> {
+    if input == Path::new("-") {
+        return Ok(Box::new(BufReader::new(tokio::io::stdin())));
+    }
+
+    let file = tokio::fs::File::open(input).await?;
+    Ok(Box::new(BufReader::new(file)))
+}
+
+fn parse_scale(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(scale) if scale.is_finite() && scale > 0.0 => Ok(scale),
+        _ => Err(format!("'{value}' is not a positive number")),
+    }
+}
+
+fn parse_max_points(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(max_points) if max_points >= 4 => Ok(max_points),
+        _ => Err(format!("'{value}' is not a number of at least 4")),
+    }
+}
+
+fn parse_max_outcomes(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(max_outcomes) if max_outcomes >= 1 => Ok(max_outcomes),
+        _ => Err(format!("'{value}' is not a number of at least 1")),
+    }
+}
+
+fn parse_precision(value: &str) -> Result<u8, String> {
+    match value.parse::<u8>() {
+        Ok(precision) if precision <= 4 => Ok(precision),
+        _ => Err(format!("'{value}' is not a number between 0 and 4")),
+    }
+}
+
+fn parse_locale(value: &str) -> Result<Locale, String> {
+    Locale::try_from(value).map_err(|_| format!("'{value}' is not a known locale"))
+}
+
+fn parse_theme(value: &str) -> Result<Theme, String> {
+    match value {
+        "dark" => Ok(Theme::dark()),
+        "light" => Ok(Theme::light()),
+        path => {
+            let json =
+                std::fs::read(path).map_err(|err| format!("Failed to read {path}: {err}"))?;
+            let theme: Theme = serde_json::from_slice(&json)
+                .map_err(|err| format!("Invalid theme in {path}: {err}"))?;
+
+            // Invalid colors would otherwise only fail the Typst compilation
+            let invalid = |err: OgImageError| format!("Invalid theme in {path}: {err}");
+            theme.validate().map_err(invalid)?;
+            Ok(theme)
+        }
+    }
+}
+
+fn init_tracing() {
+    let env_filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::ERROR.into())
+        .from_env_lossy();
+
+    fmt()
+        .compact()
+        .with_env_filter(env_filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    init_tracing();
+
+    let args = Args::parse();
+    let result = if args.batch {
+        run_batch(&args).await
+    } else {
+        run(&args).await
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: &Args) -> Result<(), String> {
+    let data = args.read_input()?;
+    let graph = data.graph();
+    let data = data.as_data(&graph);
+
+    let generator = args.generator().map_err(|err| err.to_string())?;
+    let output = generator.generate(data).await.map_err(|err| match err {
+        OgImageError::TypstCompilationError { diagnostics, .. } if !diagnostics.is_empty() => {
+            let diagnostics = diagnostics.iter().map(|d| format!("\n  {d}"));
+            format!(
+                "Typst compilation failed:{}",
+                diagnostics.collect::<String>()
+            )
+        }
+        err => err.to_string(),
+    })?;
+
+    for warning in &output.warnings {
+        eprintln!("Warning: {warning}");
+    }
+    for skipped in &output.skipped_avatars {
+        eprintln!(
+            "Warning: Skipped avatar {} ({})",
+            skipped.url, skipped.reason
+        );
+    }
+
+    // Copy the contents rather than the file, which is only readable by the
+    // current user
+    let output_path = args.output_path();
+    std::fs::read(output.image.path())
+        .and_then(|image| std::fs::write(&output_path, image))
+        .map_err(|err| format!("Failed to write {}: {err}", output_path.display()))?;
+
+    let dimensions = output
+        .dimensions
+        .map(|(width, height)| format!("{width}×{height}, "))
+        .unwrap_or_default();
+
+    println!(
+        "Wrote {} ({dimensions}{} bytes) in {} ms",
+        output_path.display(),
+        output.size,
+        output.timings.total.as_millis(),
+    );
+
+    Ok(())
+}
+
+async fn run_batch(args: &Args) -> Result<(), String> {
+    let report_path = args.report_path();
+    let display = report_path.display();
+
+    let mut options = BatchOptions::new(args.output_dir());
+    if let Some(jobs) = args.jobs {
+        options = options.with_concurrency(jobs.into());
+    }
+
+    if args.resume {
+        match tokio::fs::File::open(&report_path).await {
+            Ok(report) => {
+                let completed = read_completed_records(BufReader::new(report))
+                    .await
+                    .map_err(|err| format!("Failed to read {display}: {err}"))?;
+                options = options.with_completed(completed);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(format!("Failed to read {display}: {err}")),
+        }
+    }
+
+    let input = open_batch_input(&args.input)
+        .await
+        .map_err(|err| format!("Failed to read {}: {err}", args.input.display()))?;
+
+    if let Some(parent) = report_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    let report = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(args.resume)
+        .truncate(!args.resume)
+        .open(&report_path)
+        .await
+        .map_err(|err| format!("Failed to open {display}: {err}"))?;
+
+    let generator = args.generator().map_err(|err| err.to_string())?;
+    let summary = generator
+        .render_batch(input, report, &options)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    println!(
+        "Rendered {} images ({} degraded, {} failed, {} skipped), see {display}",
+        summary.succeeded, summary.degraded, summary.failed, summary.skipped,
+    );
+
+    match summary.failed {
+        0 => Ok(()),
+        failed => Err(format!("Failed to render {failed} records")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_args() {
+        Args::command().debug_assert();
+
+        let args = Args::parse_from(["og-image", "market.json", "-f", "svg", "-l", "square"]);
+        assert_eq!(args.format, ImageFormat::Svg);
+        assert_eq!(args.layout, Layout::Square);
+        assert_eq!(args.scale, 1.0);
+
+        assert!(Args::try_parse_from(["og-image", "--scale", "0"]).is_err());
+        assert!(Args::try_parse_from(["og-image", "--format", "gif"]).is_err());
+
+        let args = Args::parse_from(["og-image", "--theme", "light"]);
+        assert_eq!(args.theme, Some(Theme::light()));
+        assert!(Args::try_parse_from(["og-image", "--theme", "/nonexistent.json"]).is_err());
+
+        // Custom themes with invalid colors are rejected when they are loaded
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("theme.json");
+        let mut theme = serde_json::to_value(Theme::dark()).unwrap();
+        theme["accent"] = "ffb000".into();
+        std::fs::write(&path, theme.to_string()).unwrap();
+        let path = path.to_str().unwrap();
+        let err = Args::try_parse_from(["og-image", "--theme", path]).unwrap_err();
+        assert!(err.to_string().contains("accent: must be a hex color"));
+    }
+
+    #[test]
+    fn test_chart_args() {
+        let args = Args::parse_from(["og-image"]);
+        assert_eq!(args.chart_options(), ChartOptions::default());
+
+        let args = Args::parse_from([
+            "og-image",
+            "--time-scale",
+            "compressed:3600",
+            "--time-labels",
+            "start-middle-end",
+            "--timezone",
+            "Europe/Berlin",
+            "--locale",
+            "de_DE",
+            "--gridlines",
+            "--max-points",
+            "100",
+            "--downsampling",
+            "min-max",
+            "--line-style",
+            "step",
+            "--markers",
+            "last",
+            "--max-outcomes",
+            "3",
+            "--precision",
+            "1",
+        ]);
+        let expected = ChartOptions::default()
+            .with_time_scale(TimeScale::Compressed { max_gap: 3600 })
+            .with_time_labels(TimeLabels::StartMiddleEnd)
+            .with_timezone(Tz::Europe__Berlin)
+            .with_locale(Locale::de_DE)
+            .with_gridlines(true)
+            .with_max_points(100)
+            .with_downsampling(Downsampling::MinMax)
+            .with_line_style(LineStyle::Step)
+            .with_markers(Markers::Last)
+            .with_max_outcomes(3)
+            .with_precision(1);
+        assert_eq!(args.chart_options(), expected);
+
+        assert!(Args::try_parse_from(["og-image", "--timezone", "Mars/Olympus"]).is_err());
+        assert!(Args::try_parse_from(["og-image", "--locale", "xx_XX"]).is_err());
+        assert!(Args::try_parse_from(["og-image", "--max-points", "3"]).is_err());
+        assert!(Args::try_parse_from(["og-image", "--markers", "first"]).is_err());
+        assert!(Args::try_parse_from(["og-image", "--max-outcomes", "0"]).is_err());
+        assert!(Args::try_parse_from(["og-image", "--precision", "5"]).is_err());
+    }
+
+    #[test]
+    fn test_output_path() {
+        let args = Args::parse_from(["og-image", "markets/market.json"]);
+        assert_eq!(args.output_path(), Path::new("market.png"));
+
+        let args = Args::parse_from(["og-image", "--format", "pdf"]);
+        assert_eq!(args.output_path(), Path::new("og-image.pdf"));
+
+        let args = Args::parse_from(["og-image", "market.json", "-o", "out/card.png"]);
+        assert_eq!(args.output_path(), Path::new("out/card.png"));
+    }
+
+    #[test]
+    fn test_batch_args() {
+        let args = Args::parse_from(["og-image", "--batch", "markets.jsonl", "-o", "out"]);
+        assert_eq!(args.output_dir(), Path::new("out"));
+        assert_eq!(args.report_path(), Path::new("out/report.jsonl"));
+
+        let args = Args::parse_from(["og-image", "--batch", "--report", "report.jsonl"]);
+        assert_eq!(args.output_dir(), Path::new("."));
+        assert_eq!(args.report_path(), Path::new("report.jsonl"));
+
+        assert!(Args::try_parse_from(["og-image", "--resume"]).is_err());
+        assert!(Args::try_parse_from(["og-image", "--batch", "--jobs", "0"]).is_err());
+    }
+}
@@ -0,0 +1,534 @@
+//! Content-addressed caching of rendered OpenGraph images.
+//!
+//! Before compiling the Typst template, [`OgImageGenerator`] computes a
+//! [cache key](OgImageGenerator::cache_key) from the serialized input data,
+//! the template and its assets, the font configuration and the output
+//! options. If a [`RenderCache`] is configured and already contains an image
+//! for that key, the image is returned without rendering it again.
+
+use crate::chart::{ChartSeries, with_series};
+use crate::optimization::StripMode;
+use crate::{AvatarSkipReason, ImageFormat, OgImageCard, OgImageError, OgImageGenerator};
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tempfile::NamedTempFile;
+use tokio::fs;
+
+/// Version of the cache key format. Bump this to invalidate all cached images.
+const CACHE_KEY_VERSION: &str = "v1";
+
+/// Storage for rendered images, keyed by [`OgImageGenerator::cache_key()`].
+///
+/// The key already depends on the output format; the [`ImageFormat`] is
+/// passed as well, so that implementations can label the stored images,
+/// e.g. with a file extension.
+///
+/// Cache errors never fail an image generation; they are logged as warnings
+/// and the image is rendered (or returned) as if no cache was configured.
+#[async_trait]
+pub trait RenderCache: Send + Sync {
+    /// Returns the cached image for the given key, if present.
+    async fn get(&self, key: &str, format: ImageFormat) -> io::Result<Option<Vec<u8>>>;
+
+    /// Stores an image of the given format under the given key.
+    async fn put(&self, key: &str, image: &[u8], format: ImageFormat) -> io::Result<()>;
+}
+
+#[async_trait]
+impl<T: RenderCache + ?Sized> RenderCache for Arc<T> {
+    async fn get(&self, key: &str, format: ImageFormat) -> io::Result<Option<Vec<u8>>> {
+        (**self).get(key, format).await
+    }
+
+    async fn put(&self, key: &str, image: &[u8], format: ImageFormat) -> io::Result<()> {
+        (**self).put(key, image, format).await
+    }
+}
+
+/// In-memory [`RenderCache`] holding a bounded number of images.
+///
+/// Once the cache is full, the oldest entry is evicted for every new entry.
+#[derive(Debug)]
+pub struct MemoryRenderCache {
+    max_entries: usize,
+    state: Mutex<MemoryState<Arc<[u8]>>>,
+}
+
+/// Map with FIFO eviction, shared by the in-memory caches.
+#[derive(Debug)]
+struct MemoryState<V> {
+    entries: HashMap<String, V>,
+    insertion_order: VecDeque<String>,
+}
+
+impl<V> Default for MemoryState<V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            insertion_order: VecDeque::new(),
+        }
+    }
+}
+
+impl<V> MemoryState<V> {
+    fn insert(&mut self, key: &str, value: V, max_entries: usize) {
+        if max_entries == 0 {
+            return;
+        }
+
+        let previous = self.entries.insert(key.to_string(), value);
+        if previous.is_none() {
+            self.insertion_order.push_back(key.to_string());
+        }
+
+        while self.entries.len() > max_entries {
+            let Some(oldest) = self.insertion_order.pop_front() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// Locks a mutex; the cache state is always consistent, so a poisoned lock
+/// can be reused.
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+impl MemoryRenderCache {
+    /// Creates an empty cache holding at most `max_entries` images.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns the number of cached images.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns `true` if the cache contains no images.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MemoryState<Arc<[u8]>>> {
+        lock(&self.state)
+    }
+}
+
+#[async_trait]
+impl RenderCache for MemoryRenderCache {
+    async fn get(&self, key: &str, _format: ImageFormat) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.lock().entries.get(key).map(|image| image.to_vec()))
+    }
+
+    async fn put(&self, key: &str, image: &[u8], _format: ImageFormat) -> io::Result<()> {
+        self.lock().insert(key, image.into(), self.max_entries);
+        Ok(())
+    }
+}
+
+/// In-memory cache of downloaded avatars, keyed by URL.
+///
+/// Clones share the same entries, so one cache can be used by several
+/// generators or concurrent generations. Avatars that were skipped because
+/// they do not exist or have an unsupported format are cached as well;
+/// failed downloads are not. Once the cache is full, the oldest entry is
+/// evicted for every new entry.
+#[derive(Debug, Clone)]
+pub struct AvatarCache {
+    max_entries: usize,
+    state: Arc<Mutex<MemoryState<AvatarImage>>>,
+}
+
+/// Downloaded avatar, or the reason it was skipped.
+#[derive(Debug, Clone)]
+pub(crate) enum AvatarImage {
+    Image {
+        bytes: Arc<[u8]>,
+        extension: &'static str,
+    },
+    Skipped(AvatarSkipReason),
+}
+
+impl AvatarCache {
+    /// Creates an empty cache holding at most `max_entries` avatars.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            state: Arc::default(),
+        }
+    }
+
+    /// Returns the number of cached avatars.
+    pub fn len(&self) -> usize {
+        lock(&self.state).entries.len()
+    }
+
+    /// Returns `true` if the cache contains no avatars.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn get(&self, url: &str) -> Option<AvatarImage> {
+        lock(&self.state).entries.get(url).cloned()
+    }
+
+    pub(crate) fn insert(&self, url: &str, avatar: AvatarImage) {
+        lock(&self.state).insert(url, avatar, self.max_entries);
+    }
+}
+
+/// [`RenderCache`] storing images as `<key>.<extension>` files in a
+/// directory, using the [extension](ImageFormat::extension) of the image
+/// format, e.g. `<key>.svg` for SVG images.
+///
+/// The directory is created on the first write. Files are written atomically,
+/// so concurrent generators can share the same directory.
+#[derive(Debug, Clone)]
+pub struct FilesystemRenderCache {
+    directory: PathBuf,
+}
+
+impl FilesystemRenderCache {
+    /// Creates a cache storing images in the given directory.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn path(&self, key: &str, format: ImageFormat) -> io::Result<PathBuf> {
+        // Keys are hex-encoded hashes; anything else could escape the directory
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            let message = format!("Invalid render cache key: {key:?}");
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+
+        Ok(self.directory.join(format!("{key}.{}", format.extension())))
+    }
+}
+
+#[async_trait]
+impl RenderCache for FilesystemRenderCache {
+    async fn get(&self, key: &str, format: ImageFormat) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key, format)?).await {
+            Ok(image) => Ok(Some(image)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn put(&self, key: &str, image: &[u8], format: ImageFormat) -> io::Result<()> {
+        let path = self.path(key, format)?;
+        fs::create_dir_all(&self.directory).await?;
+
+        // Write to a temporary file first, so readers never see partial images
+        let temp_file = NamedTempFile::new_in(&self.directory)?;
+        fs::write(temp_file.path(), image).await?;
+        temp_file.persist(path).map_err(|err| err.error)?;
+
+        Ok(())
+    }
+}
+
+impl OgImageGenerator {
+    /// Computes the render cache key for the given data.
+    ///
+    /// The key is the hex-encoded SHA-256 hash of the card kind and its
+    /// serialized data, with the chart series of market cards prepared as
+    /// passed to the template, the template of the card kind and its assets,
+    /// the configured font path, the PNG optimization options, the theme, the
+    /// chart input of market cards, and the output format, scale and layout. Identical inputs always produce the same
+    /// key, so it can be used as a stable `ETag` (see
+    /// [`OgImageOutput::etag()`](crate::OgImageOutput::etag)).
+    ///
+    /// Avatars are identified by their URL. If the image behind an avatar URL
+    /// or the fonts in the font directory change, the cache must be cleared.
+    pub fn cache_key<'a>(&self, card: impl Into<OgImageCard<'a>>) -> Result<String, OgImageError> {
+        let card = card.into();
+        let series = self.card_series(&card);
+        let graph = series.iter().map(ChartSeries::graph).collect::<Vec<_>>();
+        let card = with_series(card, &graph);
+        let chart = self.chart_json(&card)?;
+        self.prepared_cache_key(&card, chart.as_deref())
+    }
+
+    /// Computes the render cache key of a card whose series were already
+    /// prepared with [`card_series()`](Self::card_series), given its
+    /// serialized `chart.json` input.
+    pub(crate) fn prepared_cache_key(
+        &self,
+        card: &OgImageCard<'_>,
+        chart: Option<&[u8]>,
+    ) -> Result<String, OgImageError> {
+        let kind = card.kind();
+        let json_data = serde_json::to_vec(card).map_err(OgImageError::JsonSerializationError)?;
+
+        let mut hasher = KeyHasher::default();
+        hasher.field("version", CACHE_KEY_VERSION.as_bytes());
+        hasher.field("kind", kind.as_str().as_bytes());
+        hasher.field("data", &json_data);
+
+        for (path, contents) in self.template(kind).files() {
+            hasher.field(path, contents);
+        }
+
+        let font_path = self.typst_font_path.as_ref();
+        let font_path = font_path.map(|path| path.to_string_lossy());
+        hasher.field("font_path", font_path.as_deref().unwrap_or("").as_bytes());
+
+        let optimization = &self.optimization;
+        let strip = match optimization.strip {
+            StripMode::None => "none",
+            StripMode::Safe => "safe",
+            StripMode::All => "all",
+        };
+        let options = format!(
+            "enabled={};level={};strip={strip};zopfli={}",
+            optimization.enabled,
+            optimization.effective_level(),
+            optimization.zopfli,
+        );
+        hasher.field("optimization", options.as_bytes());
+
+        hasher.field("format", self.format.extension().as_bytes());
+        hasher.field("scale", self.scale.to_string().as_bytes());
+        hasher.field("layout", self.layout.as_str().as_bytes());
+
+        let theme =
+            serde_json::to_vec(&self.theme).map_err(OgImageError::JsonSerializationError)?;
+        hasher.field("theme", &theme);
+
+        // Market cards are hashed as passed to the template, after preparing
+        // the series, so every chart option that changes the image changes
+        // the key
+        if let Some(chart) = chart {
+            hasher.field("chart", chart);
+        }
+
+        // Relative times like "Ends in 3d" change with the current time
+        if let OgImageCard::Market(data) = &card
+            && let Some(timing) = self.market_timing(data)
+        {
+            hasher.field("timing", timing.as_bytes());
+        }
+
+        Ok(hasher.finish())
+    }
+}
+
+/// SHA-256 hasher that length-prefixes every field to avoid ambiguous inputs.
+#[derive(Default)]
+struct KeyHasher(Sha256);
+
+impl KeyHasher {
+    fn field(&mut self, name: &str, value: &[u8]) {
+        for part in [name.as_bytes(), value] {
+            self.0.update((part.len() as u64).to_le_bytes());
+            self.0.update(part);
+        }
+    }
+
+    fn finish(self) -> String {
+        format!("{:x}", self.0.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CardKind, ChartOptions, ImageFormat, Layout, OgImageAuthorData, OgImageCommunityData,
+        OgImageData, OgImageDataPoint, OgImageGraphData, OgImageMarketKind, OptimizationConfig,
+        Template, Theme,
+    };
+
+    fn test_data(question: &str) -> OgImageData<'_> {
+        OgImageData {
+            question,
+            author: OgImageAuthorData::new("test-user", "https://example.com/user.png"),
+            community: OgImageCommunityData::new("test", "https://example.com/community.png"),
+            outcome: "NONE",
+            kind: OgImageMarketKind::Probability,
+            graph: &[],
+            closes_at: None,
+            resolved_at: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_key_chart_series() {
+        let points = (0..200)
+            .map(|time| OgImageDataPoint {
+                time,
+                value: (time % 7) as f64,
+            })
+            .collect::<Vec<_>>();
+        let graph = [OgImageGraphData {
+            outcome: "Yes",
+            color: "#00f29c",
+            data: &points,
+        }];
+        let data = OgImageData {
+            graph: &graph,
+            ..test_data("Question?")
+        };
+
+        let key = OgImageGenerator::default().cache_key(data.clone()).unwrap();
+
+        // Downsampling changes the series passed to the template
+        let chart = ChartOptions::default().with_max_points(100);
+        let generator = OgImageGenerator::default().with_chart(chart);
+        assert_ne!(generator.cache_key(data.clone()).unwrap(), key);
+
+        // Options without an effect on the image do not change the key
+        let chart = ChartOptions::default().with_max_points(1000);
+        let generator = OgImageGenerator::default().with_chart(chart);
+        assert_eq!(generator.cache_key(data.clone()).unwrap(), key);
+    }
+
+    #[test]
+    fn test_cache_key_market_timing() {
+        let data = OgImageData {
+            closes_at: Some(1_745_229_600),
+            ..test_data("Question?")
+        };
+
+        // The key only changes when the relative time shown on the card does
+        let generator = OgImageGenerator::default().with_now(1_745_229_600 - 3 * 24 * 60 * 60);
+        let key = generator.cache_key(data.clone()).unwrap();
+        let generator = generator.with_now(1_745_229_600 - 3 * 24 * 60 * 60 - 60);
+        assert_eq!(generator.cache_key(data.clone()).unwrap(), key);
+        let generator = generator.with_now(1_745_229_600 - 2 * 24 * 60 * 60);
+        assert_ne!(generator.cache_key(data.clone()).unwrap(), key);
+
+        // Data without timestamps does not depend on the current time
+        let key = generator.cache_key(test_data("Question?")).unwrap();
+        let generator = generator.with_now(0);
+        assert_eq!(generator.cache_key(test_data("Question?")).unwrap(), key);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let generator = OgImageGenerator::default();
+        let key = generator.cache_key(test_data("Question?")).unwrap();
+        assert_eq!(key.len(), 64);
+        assert_eq!(generator.cache_key(test_data("Question?")).unwrap(), key);
+
+        let other_data = generator.cache_key(test_data("Other question?")).unwrap();
+        assert_ne!(other_data, key);
+
+        let generator = OgImageGenerator::default().with_font_path(PathBuf::from("/fonts"));
+        let other_fonts = generator.cache_key(test_data("Question?")).unwrap();
+        assert_ne!(other_fonts, key);
+
+        let generator = OgImageGenerator::default().with_optimization(OptimizationConfig::off());
+        let other_options = generator.cache_key(test_data("Question?")).unwrap();
+        assert_ne!(other_options, key);
+
+        let generator = OgImageGenerator::default().with_format(ImageFormat::Svg);
+        assert_ne!(generator.cache_key(test_data("Question?")).unwrap(), key);
+
+        let generator = OgImageGenerator::default().with_scale(2.0);
+        assert_ne!(generator.cache_key(test_data("Question?")).unwrap(), key);
+
+        let generator = OgImageGenerator::default().with_layout(Layout::Square);
+        assert_ne!(generator.cache_key(test_data("Question?")).unwrap(), key);
+
+        let generator = OgImageGenerator::default().with_theme(Theme::light());
+        assert_ne!(generator.cache_key(test_data("Question?")).unwrap(), key);
+
+        let chart = ChartOptions::default().with_gridlines(true);
+        let generator = OgImageGenerator::default().with_chart(chart);
+        assert_ne!(generator.cache_key(test_data("Question?")).unwrap(), key);
+
+        let template = Template::market().with_asset("assets/extra.svg", b"<svg/>".as_slice());
+        let generator = OgImageGenerator::default().with_template(template);
+        assert_ne!(generator.cache_key(test_data("Question?")).unwrap(), key);
+
+        // Only the template of the rendered card kind is part of the key
+        let template = Template::profile().with_asset("assets/extra.svg", b"<svg/>".as_slice());
+        let generator = OgImageGenerator::default().with_card_template(CardKind::Profile, template);
+        assert_eq!(generator.cache_key(test_data("Question?")).unwrap(), key);
+
+        // The binary paths do not affect the rendered image
+        let generator = OgImageGenerator::default().with_typst_path(PathBuf::from("/bin/typst"));
+        assert_eq!(generator.cache_key(test_data("Question?")).unwrap(), key);
+    }
+
+    #[tokio::test]
+    async fn test_memory_render_cache() {
+        let cache = MemoryRenderCache::new(2);
+        assert!(cache.is_empty());
+        assert_eq!(cache.get("a", ImageFormat::Png).await.unwrap(), None);
+
+        cache.put("a", b"image a", ImageFormat::Png).await.unwrap();
+        cache.put("b", b"image b", ImageFormat::Png).await.unwrap();
+        assert_eq!(
+            cache.get("a", ImageFormat::Png).await.unwrap().as_deref(),
+            Some(&b"image a"[..])
+        );
+
+        // Adding a third image evicts the oldest one
+        cache.put("c", b"image c", ImageFormat::Png).await.unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a", ImageFormat::Png).await.unwrap(), None);
+        assert_eq!(
+            cache.get("c", ImageFormat::Png).await.unwrap().as_deref(),
+            Some(&b"image c"[..])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_render_cache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache = FilesystemRenderCache::new(temp_dir.path().join("cache"));
+
+        assert_eq!(cache.get("abc123", ImageFormat::Png).await.unwrap(), None);
+
+        cache
+            .put("abc123", b"image", ImageFormat::Png)
+            .await
+            .unwrap();
+        assert_eq!(
+            cache
+                .get("abc123", ImageFormat::Png)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(&b"image"[..])
+        );
+        assert!(temp_dir.path().join("cache/abc123.png").exists());
+
+        // Images are stored with the extension of their format
+        cache
+            .put("abc123", b"<svg/>", ImageFormat::Svg)
+            .await
+            .unwrap();
+        assert!(temp_dir.path().join("cache/abc123.svg").exists());
+        assert_eq!(
+            cache
+                .get("abc123", ImageFormat::Svg)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(&b"<svg/>"[..])
+        );
+        assert_eq!(cache.get("abc123", ImageFormat::Pdf).await.unwrap(), None);
+
+        let err = cache
+            .put("../escape", b"image", ImageFormat::Png)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,256 @@
+//! Card kinds rendered by the generator.
+//!
+//! Besides prediction markets, OpenGraph images can be rendered for user
+//! profiles, community pages and shared comments. Each [`CardKind`] is
+//! rendered with its own [`Template`](crate::Template), and the data of a
+//! single image is passed to the generator as an [`OgImageCard`].
+
+use crate::formatting::{serialize_number, serialize_optional_number, serialize_signed_dollars};
+use crate::{OgImageAuthorData, OgImageCommunityData, OgImageData};
+use serde::Serialize;
+use std::fmt;
+
+/// Kind of entity an OpenGraph image is rendered for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CardKind {
+    /// A prediction market, see [`OgImageData`]
+    Market,
+    /// A user profile, see [`OgImageProfileData`]
+    Profile,
+    /// A community page, see [`OgImageCommunityPageData`]
+    Community,
+    /// A comment on a prediction market, see [`OgImageCommentData`]
+    Comment,
+}
+
+impl CardKind {
+    /// All card kinds.
+    pub const ALL: [CardKind; 4] = [Self::Market, Self::Profile, Self::Community, Self::Comment];
+
+    /// Returns the lowercase name of the card kind.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Market => "market",
+            Self::Profile => "profile",
+            Self::Community => "community",
+            Self::Comment => "comment",
+        }
+    }
+}
+
+impl fmt::Display for CardKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Data of a user profile card.
+#[derive(Debug, Clone, Serialize)]
+pub struct OgImageProfileData<'a> {
+    /// Username
+    pub name: &'a str,
+    /// Avatar URL
+    pub avatar: &'a str,
+    /// Number of predictions made by the user
+    #[serde(serialize_with = "serialize_number")]
+    pub predictions: u32,
+    /// Share of resolved predictions that were won, in percent
+    pub win_rate: Option<u32>,
+    /// Total profit or loss in cents
+    #[serde(serialize_with = "serialize_signed_dollars")]
+    pub profit_loss: i64,
+}
+
+/// Data of a community page card.
+#[derive(Debug, Clone, Serialize)]
+pub struct OgImageCommunityPageData<'a> {
+    /// Community information
+    pub community: OgImageCommunityData<'a>,
+    /// Number of community members
+    #[serde(serialize_with = "serialize_number")]
+    pub members: u32,
+    /// Most popular markets of the community, of which the first three are shown
+    pub top_markets: &'a [OgImageMarketSummary<'a>],
+}
+
+/// Summary of a market, as listed on a community page card.
+#[derive(Debug, Clone, Serialize)]
+pub struct OgImageMarketSummary<'a> {
+    /// The prediction market question
+    pub question: &'a str,
+    /// Trading volume in dollars, if known
+    #[serde(serialize_with = "serialize_optional_number")]
+    pub volume: Option<u32>,
+}
+
+/// Data of a comment share card.
+#[derive(Debug, Clone, Serialize)]
+pub struct OgImageCommentData<'a> {
+    /// Author of the comment
+    pub author: OgImageAuthorData<'a>,
+    /// Community of the market the comment was posted on
+    pub community: OgImageCommunityData<'a>,
+    /// Question of the market the comment was posted on
+    pub question: &'a str,
+    /// Text of the comment
+    pub body: &'a str,
+    /// Number of likes of the comment
+    #[serde(serialize_with = "serialize_number")]
+    pub likes: u32,
+}
+
+/// Data of an OpenGraph image of any [`CardKind`].
+///
+/// All card data types convert into `OgImageCard`, so they can be passed
+/// directly to [`OgImageGenerator::generate()`](crate::OgImageGenerator::generate).
+/// The card is serialized as its inner data.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum OgImageCard<'a> {
+    Market(OgImageData<'a>),
+    Profile(OgImageProfileData<'a>),
+    Community(OgImageCommunityPageData<'a>),
+    Comment(OgImageCommentData<'a>),
+}
+
+/// An image of a card that is downloaded into the assets directory.
+pub(crate) struct CardImage<'a> {
+    /// URL of the image
+    pub url: &'a str,
+    /// File name of the downloaded image, without extension
+    pub file_stem: &'static str,
+    /// Whether a failed download is skipped instead of failing the render
+    pub optional: bool,
+}
+
+impl<'a> CardImage<'a> {
+    const fn required(url: &'a str, file_stem: &'static str) -> Self {
+        Self {
+            url,
+            file_stem,
+            optional: false,
+        }
+    }
+}
+
+impl<'a> OgImageCard<'a> {
+    /// Returns the kind of the card.
+    pub fn kind(&self) -> CardKind {
+        match self {
+            Self::Market(_) => CardKind::Market,
+            Self::Profile(_) => CardKind::Profile,
+            Self::Community(_) => CardKind::Community,
+            Self::Comment(_) => CardKind::Comment,
+        }
+    }
+
+    /// Returns the avatars and banners shown on the card.
+    pub(crate) fn images(&self) -> Vec<CardImage<'a>> {
+        let (author, community) = match self {
+            Self::Market(data) => (Some(&data.author), Some(&data.community)),
+            Self::Profile(data) => {
+                return vec![CardImage::required(data.avatar, "profile_avatar")];
+            }
+            Self::Community(data) => (None, Some(&data.community)),
+            Self::Comment(data) => (Some(&data.author), Some(&data.community)),
+        };
+
+        let mut images = Vec::new();
+        if let Some(author) = author {
+            images.push(CardImage::required(author.avatar, "author_avatar"));
+        }
+        if let Some(community) = community {
+            images.push(CardImage::required(community.avatar, "community_avatar"));
+            if let Some(banner) = community.banner {
+                images.push(CardImage {
+                    url: banner,
+                    file_stem: "community_banner",
+                    optional: true,
+                });
+            }
+        }
+        images
+    }
+}
+
+impl<'a> From<OgImageData<'a>> for OgImageCard<'a> {
+    fn from(data: OgImageData<'a>) -> Self {
+        Self::Market(data)
+    }
+}
+
+impl<'a> From<&OgImageData<'a>> for OgImageCard<'a> {
+    fn from(data: &OgImageData<'a>) -> Self {
+        Self::Market(data.clone())
+    }
+}
+
+impl<'a> From<OgImageProfileData<'a>> for OgImageCard<'a> {
+    fn from(data: OgImageProfileData<'a>) -> Self {
+        Self::Profile(data)
+    }
+}
+
+impl<'a> From<OgImageCommunityPageData<'a>> for OgImageCard<'a> {
+    fn from(data: OgImageCommunityPageData<'a>) -> Self {
+        Self::Community(data)
+    }
+}
+
+impl<'a> From<OgImageCommentData<'a>> for OgImageCard<'a> {
+    fn from(data: OgImageCommentData<'a>) -> Self {
+        Self::Comment(data)
+    }
+}
+
+impl<'a> From<&OgImageCard<'a>> for OgImageCard<'a> {
+    fn from(card: &OgImageCard<'a>) -> Self {
+        card.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_card_json() {
+        let card = OgImageCard::from(OgImageProfileData {
+            name: "alice",
+            avatar: "https://example.com/alice.png",
+            predictions: 1500,
+            win_rate: Some(64),
+            profit_loss: -35000,
+        });
+        assert_eq!(card.kind(), CardKind::Profile);
+
+        let json = serde_json::to_value(&card).unwrap();
+        assert_eq!(json["predictions"], "1.5K");
+        assert_eq!(json["win_rate"], 64);
+        assert_eq!(json["profit_loss"], "-$350");
+    }
+
+    #[test]
+    fn test_card_images() {
+        let community = OgImageCommunityData::new("climate", "https://example.com/climate.png")
+            .with_banner("https://example.com/banner.png");
+        let card = OgImageCard::from(OgImageCommentData {
+            author: OgImageAuthorData::new("alice", "https://example.com/alice.png"),
+            community,
+            question: "Will it rain tomorrow?",
+            body: "Definitely.",
+            likes: 3,
+        });
+
+        let images = card.images();
+        let stems = images
+            .iter()
+            .map(|image| image.file_stem)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stems,
+            ["author_avatar", "community_avatar", "community_banner"]
+        );
+        assert!(images[2].optional);
+    }
+}
@@ -0,0 +1,1136 @@
+//! Options of the probability chart of market images.
+
+use crate::downsample::downsample;
+use crate::formatting::{MAX_DECIMALS, format_percent};
+use crate::{
+    OgImageCard, OgImageData, OgImageDataPoint, OgImageError, OgImageGenerator, OgImageGraphData,
+    OgImageMarketKind,
+};
+use chrono::{DateTime, Locale, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Positions of the horizontal gridlines, in percent of the value range.
+const GRIDLINES: &[u32] = &[25, 50, 75];
+
+/// Default maximum number of data points per series.
+const DEFAULT_MAX_POINTS: usize = 500;
+
+/// Default maximum number of outcomes drawn as separate series.
+const DEFAULT_MAX_OUTCOMES: usize = 4;
+
+/// Colors assigned to series without a valid color, in order, skipping the
+/// colors of other series.
+const PALETTE: &[&str] = &[
+    "#00F29C", "#FF6B6B", "#4DA3FF", "#FFC542", "#B57BFF", "#FF8FD8", "#4DE8E8", "#FF9F43",
+];
+
+/// Outcome and color of the series aggregating the remaining outcomes.
+const OTHER_OUTCOME: &str = "Other";
+const OTHER_COLOR: &str = "#8A9A94";
+
+const SECONDS_PER_HOUR: f64 = 60. * 60.;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// How the times of the data points are mapped to the time axis of the chart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeScale {
+    /// Data points are placed according to their real time
+    #[default]
+    Real,
+    /// Like [`Real`](Self::Real), but gaps between consecutive data points
+    /// longer than `max_gap` seconds are shortened to `max_gap` seconds, so
+    /// long periods of inactivity do not flatten the rest of the chart
+    Compressed {
+        /// Longest gap between two data points on the time axis, in seconds
+        max_gap: u64,
+    },
+    /// Consecutive data points are placed at equal distances, regardless of
+    /// the time between them
+    Ordinal,
+}
+
+impl fmt::Display for TimeScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Real => f.write_str("real"),
+            Self::Compressed { max_gap } => write!(f, "compressed:{max_gap}"),
+            Self::Ordinal => f.write_str("ordinal"),
+        }
+    }
+}
+
+impl FromStr for TimeScale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "real" => Ok(Self::Real),
+            None if s == "ordinal" => Ok(Self::Ordinal),
+            Some(("compressed", max_gap)) => match max_gap.parse() {
+                Ok(max_gap) => Ok(Self::Compressed { max_gap }),
+                Err(_) => Err(format!("Invalid maximum gap '{max_gap}', expected seconds")),
+            },
+            _ => Err(format!(
+                "Unknown time scale '{s}', expected 'real', 'compressed:<seconds>' or 'ordinal'"
+            )),
+        }
+    }
+}
+
+/// Algorithm used to reduce series with more than the
+/// [maximum number of points](ChartOptions::max_points).
+///
+/// Both algorithms keep the first and the last data point of each series.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Downsampling {
+    /// Largest-Triangle-Three-Buckets, which keeps the visual shape of the
+    /// series, including peaks and trend changes
+    #[default]
+    Lttb,
+    /// Keeps the lowest and the highest value of each bucket of data points,
+    /// which preserves the full value range of the series
+    MinMax,
+}
+
+impl Downsampling {
+    /// Returns the name of the algorithm, as accepted by [`FromStr`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lttb => "lttb",
+            Self::MinMax => "min-max",
+        }
+    }
+}
+
+impl fmt::Display for Downsampling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Downsampling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lttb" => Ok(Self::Lttb),
+            "min-max" => Ok(Self::MinMax),
+            _ => Err(format!(
+                "Unknown downsampling '{s}', expected 'lttb' or 'min-max'"
+            )),
+        }
+    }
+}
+
+/// Which dates are shown below the probability chart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeLabels {
+    /// No date labels
+    #[default]
+    None,
+    /// The dates of the first and the last data point
+    StartEnd,
+    /// The start and end dates, and the date halfway between them
+    StartMiddleEnd,
+}
+
+impl TimeLabels {
+    /// Returns the name of the option, as accepted by [`FromStr`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::StartEnd => "start-end",
+            Self::StartMiddleEnd => "start-middle-end",
+        }
+    }
+}
+
+impl fmt::Display for TimeLabels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TimeLabels {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "start-end" => Ok(Self::StartEnd),
+            "start-middle-end" => Ok(Self::StartMiddleEnd),
+            _ => Err(format!(
+                "Unknown time labels '{s}', expected 'none', 'start-end' or 'start-middle-end'"
+            )),
+        }
+    }
+}
+
+/// How the series of the probability chart are drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineStyle {
+    /// Straight lines between consecutive data points
+    #[default]
+    Straight,
+    /// Horizontal lines that step to the next value at the time of each
+    /// data point, matching the discrete price changes of trades
+    Step,
+}
+
+impl LineStyle {
+    /// Returns the name of the line style, as accepted by [`FromStr`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Straight => "straight",
+            Self::Step => "step",
+        }
+    }
+}
+
+impl fmt::Display for LineStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LineStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "straight" => Ok(Self::Straight),
+            "step" => Ok(Self::Step),
+            _ => Err(format!(
+                "Unknown line style '{s}', expected 'straight' or 'step'"
+            )),
+        }
+    }
+}
+
+/// Which data points of the chart series are marked with a dot.
+///
+/// Regardless of the markers, every series is extended to the end of the
+/// chart, where it ends in a dot labeled with its current value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Markers {
+    /// No markers on the data points
+    None,
+    /// Only the last data point, showing when the series last changed
+    Last,
+    /// Every data point
+    #[default]
+    All,
+}
+
+impl Markers {
+    /// Returns the name of the marker policy, as accepted by [`FromStr`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Last => "last",
+            Self::All => "all",
+        }
+    }
+}
+
+impl fmt::Display for Markers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Markers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "last" => Ok(Self::Last),
+            "all" => Ok(Self::All),
+            _ => Err(format!(
+                "Unknown markers '{s}', expected 'none', 'last' or 'all'"
+            )),
+        }
+    }
+}
+
+/// Configuration of the probability chart of market images.
+///
+/// By default the chart is drawn on a [real](TimeScale::Real) time axis,
+/// without date labels or gridlines, and series with more than 500 data
+/// points are [downsampled](Downsampling::Lttb). At most four outcomes are
+/// drawn, the remaining ones are combined into an "Other" series, and
+/// outcomes without a valid hex color get an unused one from a builtin
+/// palette. Series are drawn as [straight](LineStyle::Straight) lines with a
+/// marker on [every](Markers::All) data point.
+///
+/// Date labels are formatted from the [`time`](crate::OgImageDataPoint::time)
+/// of the data points in the configured timezone and locale. Depending on
+/// the time span of the chart, they show the time of day (less than a day),
+/// the day and month (less than a year) or the month and year.
+///
+/// # Examples
+///
+/// ```
+/// use crates_io_og_image::{
+///     ChartOptions, LineStyle, Locale, Markers, OgImageGenerator, TimeLabels, Tz,
+/// };
+///
+/// let timezone: Tz = "Europe/Berlin".parse().unwrap();
+/// let generator = OgImageGenerator::default().with_chart(
+///     ChartOptions::default()
+///         .with_time_labels(TimeLabels::StartMiddleEnd)
+///         .with_timezone(timezone)
+///         .with_locale(Locale::de_DE)
+///         .with_gridlines(true)
+///         .with_line_style(LineStyle::Step)
+///         .with_markers(Markers::Last),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartOptions {
+    /// How the times of the data points are mapped to the time axis
+    pub time_scale: TimeScale,
+    /// Which dates are shown below the chart
+    pub time_labels: TimeLabels,
+    /// Timezone of the date labels and of the closing dates of markets
+    pub timezone: Tz,
+    /// Locale of the month names in the date labels
+    pub locale: Locale,
+    /// Whether horizontal gridlines are drawn at 25%, 50% and 75% of the
+    /// value range
+    pub gridlines: bool,
+    /// Maximum number of data points per series, longer series are downsampled
+    pub max_points: usize,
+    /// Algorithm used to downsample long series
+    pub downsampling: Downsampling,
+    /// How the series are drawn
+    pub line_style: LineStyle,
+    /// Which data points are marked with a dot
+    pub markers: Markers,
+    /// Maximum number of outcomes drawn as separate series, ordered by
+    /// their current probability
+    pub max_outcomes: usize,
+    /// Number of decimal places of the probabilities in the labels, at most 4
+    pub precision: u8,
+}
+
+impl ChartOptions {
+    /// Sets how the times of the data points are mapped to the time axis.
+    pub const fn with_time_scale(mut self, time_scale: TimeScale) -> Self {
+        self.time_scale = time_scale;
+        self
+    }
+
+    /// Sets which dates are shown below the chart.
+    pub const fn with_time_labels(mut self, time_labels: TimeLabels) -> Self {
+        self.time_labels = time_labels;
+        self
+    }
+
+    /// Sets the timezone of the date labels and of the closing dates of markets.
+    pub const fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Sets the locale of the month names in the date labels.
+    pub const fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Sets whether horizontal gridlines are drawn at 25%, 50% and 75% of the
+    /// value range.
+    pub const fn with_gridlines(mut self, gridlines: bool) -> Self {
+        self.gridlines = gridlines;
+        self
+    }
+
+    /// Sets the maximum number of data points per series.
+    ///
+    /// Series with more data points are reduced with the configured
+    /// [downsampling](Self::with_downsampling) algorithm before rendering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_points` is less than 4.
+    pub const fn with_max_points(mut self, max_points: usize) -> Self {
+        assert!(max_points >= 4, "max_points must be at least 4");
+        self.max_points = max_points;
+        self
+    }
+
+    /// Sets the algorithm used to downsample long series.
+    pub const fn with_downsampling(mut self, downsampling: Downsampling) -> Self {
+        self.downsampling = downsampling;
+        self
+    }
+
+    /// Sets how the series are drawn.
+    pub const fn with_line_style(mut self, line_style: LineStyle) -> Self {
+        self.line_style = line_style;
+        self
+    }
+
+    /// Sets which data points are marked with a dot.
+    pub const fn with_markers(mut self, markers: Markers) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    /// Sets the maximum number of outcomes drawn as separate series.
+    ///
+    /// If a market has more outcomes, only the most likely ones are drawn,
+    /// and the remaining outcomes are combined into a single "Other" series
+    /// with their summed probability. A single remaining outcome is drawn
+    /// as is. The values of [scalar](OgImageMarketKind::Scalar) markets
+    /// can't be summed, so only the outcomes with the highest values are
+    /// drawn, without an "Other" series.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_outcomes` is zero.
+    pub const fn with_max_outcomes(mut self, max_outcomes: usize) -> Self {
+        assert!(max_outcomes >= 1, "max_outcomes must be at least 1");
+        self.max_outcomes = max_outcomes;
+        self
+    }
+
+    /// Sets the number of decimal places of the probabilities in the labels.
+    ///
+    /// Probabilities that would be rounded to 0% or 100% are shown as "<1%"
+    /// or ">99%", or e.g. "<0.1%" with one decimal place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `precision` is greater than 4.
+    pub const fn with_precision(mut self, precision: u8) -> Self {
+        assert!(precision <= MAX_DECIMALS, "precision must be at most 4");
+        self.precision = precision;
+        self
+    }
+
+    /// Returns the series of the chart for a market of the given kind,
+    /// ordered by their current value.
+    ///
+    /// The data points of each series are ordered by time and downsampled to
+    /// the maximum number of points, outcomes beyond the maximum number of
+    /// outcomes are combined or, for scalar markets, left out, and missing or
+    /// invalid colors are replaced by palette colors of no other series.
+    pub(crate) fn series<'a>(
+        &self,
+        graph: &[OgImageGraphData<'a>],
+        kind: &OgImageMarketKind<'_>,
+    ) -> Vec<ChartSeries<'a>> {
+        // Series without a valid color get the palette colors that are not
+        // used by another series, in order, and all of them once every
+        // palette color is used
+        let used = graph.iter().map(|series| series.color);
+        let used = used.filter(|color| is_hex_color(color)).collect::<Vec<_>>();
+        let mut palette = PALETTE.to_vec();
+        palette.retain(|color| !used.iter().any(|used| same_color(used, color)));
+        if palette.is_empty() {
+            palette = PALETTE.to_vec();
+        }
+        let mut palette = palette.into_iter().cycle();
+
+        let mut series = graph
+            .iter()
+            .map(|series| {
+                let mut data = series.data.to_vec();
+                data.sort_by_key(|point| point.time);
+                let color = match is_hex_color(series.color) {
+                    true => series.color,
+                    false => palette.next().unwrap_or(OTHER_COLOR),
+                };
+                ChartSeries {
+                    outcome: series.outcome,
+                    color,
+                    data,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let last_value = |series: &ChartSeries<'_>| {
+            let last = series.data.last();
+            last.map_or(f64::NEG_INFINITY, |point| point.value)
+        };
+        series.sort_by(|a, b| last_value(b).total_cmp(&last_value(a)));
+        match kind {
+            OgImageMarketKind::Probability if series.len() > self.max_outcomes + 1 => {
+                let other = series.split_off(self.max_outcomes);
+
+                // The "Other" series is gray, unless a shown series is as well
+                let colors = std::iter::once(OTHER_COLOR).chain(PALETTE.iter().copied());
+                let mut colors = colors.filter(|color| {
+                    let mut shown = series.iter();
+                    !shown.any(|series| same_color(series.color, color))
+                });
+                series.push(ChartSeries {
+                    outcome: OTHER_OUTCOME,
+                    color: colors.next().unwrap_or(OTHER_COLOR),
+                    data: aggregate(&other),
+                });
+            }
+            OgImageMarketKind::Probability => {}
+            OgImageMarketKind::Scalar(_) => series.truncate(self.max_outcomes),
+        }
+
+        for series in &mut series {
+            if series.data.len() > self.max_points {
+                series.data = downsample(&series.data, self.max_points, self.downsampling);
+            }
+        }
+        series
+    }
+
+    /// Returns the chart input of the template for the given graph of a
+    /// market of the given kind.
+    pub(crate) fn input(
+        &self,
+        graph: &[OgImageGraphData<'_>],
+        kind: &OgImageMarketKind<'_>,
+    ) -> ChartInput {
+        let (min, max) = kind.range();
+        let gridlines = match self.gridlines {
+            true => GRIDLINES
+                .iter()
+                .map(|&percent| scale(min, max, percent))
+                .collect(),
+            false => Vec::new(),
+        };
+        let time_axis = self.time_axis(graph);
+        let time_labels = self.time_labels(&time_axis);
+
+        let values = graph.iter().map(|series| {
+            let last = series.data.iter().max_by_key(|point| point.time);
+            last.map(|point| match kind {
+                OgImageMarketKind::Probability => format_percent(point.value, self.precision),
+                OgImageMarketKind::Scalar(scalar) => scalar.format_value(point.value),
+            })
+        });
+        let values = values.collect::<Vec<_>>();
+        let headline = match kind {
+            OgImageMarketKind::Probability => None,
+            OgImageMarketKind::Scalar(_) => values.first().cloned().flatten(),
+        };
+
+        ChartInput {
+            value_axis: ValueAxis { min, max },
+            gridlines,
+            time_axis,
+            time_labels,
+            line_style: self.line_style.as_str(),
+            markers: self.markers.as_str(),
+            values,
+            headline,
+        }
+    }
+
+    /// Returns the position on the time axis of every distinct timestamp of
+    /// the graph, ordered by time.
+    ///
+    /// Positions are measured in hours since the first data point, or in
+    /// data points for the [ordinal](TimeScale::Ordinal) time scale.
+    fn time_axis(&self, graph: &[OgImageGraphData<'_>]) -> Vec<(u64, f64)> {
+        let times = graph.iter().flat_map(|series| series.data.iter());
+        let mut times = times.map(|point| point.time).collect::<Vec<_>>();
+        times.sort_unstable();
+        times.dedup();
+
+        let Some(&start) = times.first() else {
+            return Vec::new();
+        };
+
+        let mut previous = start;
+        let mut position = 0.;
+        times
+            .into_iter()
+            .enumerate()
+            .map(|(index, time)| {
+                position = match self.time_scale {
+                    TimeScale::Real => (time - start) as f64 / SECONDS_PER_HOUR,
+                    TimeScale::Compressed { max_gap } => {
+                        position + (time - previous).min(max_gap) as f64 / SECONDS_PER_HOUR
+                    }
+                    TimeScale::Ordinal => index as f64,
+                };
+                previous = time;
+                (time, position)
+            })
+            .collect()
+    }
+
+    fn time_labels(&self, time_axis: &[(u64, f64)]) -> Vec<TimeLabel> {
+        let (Some(&(start, _)), Some(&(end, _))) = (time_axis.first(), time_axis.last()) else {
+            return Vec::new();
+        };
+
+        let times = match self.time_labels {
+            TimeLabels::None => return Vec::new(),
+            TimeLabels::StartEnd => vec![start, end],
+            TimeLabels::StartMiddleEnd => vec![start, start + (end - start) / 2, end],
+        };
+
+        let format = match end - start {
+            span if span < SECONDS_PER_DAY => "%H:%M",
+            span if span < 365 * SECONDS_PER_DAY => "%-d %b",
+            _ => "%b %Y",
+        };
+
+        times
+            .into_iter()
+            .filter_map(|time| {
+                let datetime = DateTime::<Utc>::from_timestamp(time.try_into().ok()?, 0)?;
+                let datetime = self.timezone.from_utc_datetime(&datetime.naive_utc());
+                let label = datetime.format_localized(format, self.locale).to_string();
+                let x = axis_position(time_axis, time);
+                Some(TimeLabel { time, x, label })
+            })
+            .collect()
+    }
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        Self {
+            time_scale: TimeScale::Real,
+            time_labels: TimeLabels::None,
+            timezone: Tz::UTC,
+            locale: Locale::en_US,
+            gridlines: false,
+            max_points: DEFAULT_MAX_POINTS,
+            downsampling: Downsampling::Lttb,
+            line_style: LineStyle::Straight,
+            markers: Markers::All,
+            max_outcomes: DEFAULT_MAX_OUTCOMES,
+            precision: 0,
+        }
+    }
+}
+
+/// A series of the chart, as passed to the template.
+#[derive(Debug)]
+pub(crate) struct ChartSeries<'a> {
+    pub outcome: &'a str,
+    pub color: &'a str,
+    /// Data points ordered by time
+    pub data: Vec<OgImageDataPoint>,
+}
+
+impl ChartSeries<'_> {
+    /// Returns the series as graph data of the template input.
+    pub(crate) fn graph(&self) -> OgImageGraphData<'_> {
+        OgImageGraphData {
+            outcome: self.outcome,
+            color: self.color,
+            data: &self.data,
+        }
+    }
+}
+
+/// Returns whether two hex colors are the same color, like `#0f9` and
+/// `#00FF99`.
+fn same_color(a: &str, b: &str) -> bool {
+    let expand = |color: &str| {
+        let hex = color.trim_start_matches('#').to_ascii_lowercase();
+        match hex.len() {
+            3 | 4 => hex.chars().flat_map(|c| [c, c]).collect(),
+            _ => hex,
+        }
+    };
+    expand(a) == expand(b)
+}
+
+/// Returns whether the color is a hex color accepted by the template, like
+/// `#0f9` or `#00ff99`.
+pub(crate) fn is_hex_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => {
+            matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+/// Combines series into a single series with the summed value at every
+/// timestamp of any of the series.
+///
+/// Each series contributes its most recent value at a timestamp, or nothing
+/// before its first data point.
+fn aggregate(series: &[ChartSeries<'_>]) -> Vec<OgImageDataPoint> {
+    let times = series.iter().flat_map(|series| series.data.iter());
+    let mut times = times.map(|point| point.time).collect::<Vec<_>>();
+    times.sort_unstable();
+    times.dedup();
+
+    let mut next = vec![0; series.len()];
+    times
+        .into_iter()
+        .map(|time| {
+            let value = series
+                .iter()
+                .zip(&mut next)
+                .map(|(series, next)| {
+                    while series
+                        .data
+                        .get(*next)
+                        .is_some_and(|point| point.time <= time)
+                    {
+                        *next += 1;
+                    }
+                    next.checked_sub(1)
+                        .map_or(0., |index| series.data[index].value)
+                })
+                .sum();
+            OgImageDataPoint { time, value }
+        })
+        .collect()
+}
+
+/// Returns the value at the given percentage of the range.
+fn scale(min: f64, max: f64, percent: u32) -> f64 {
+    min + (max - min) * f64::from(percent) / 100.
+}
+
+/// Returns the position of a timestamp on the time axis, interpolating
+/// between the neighboring data points.
+fn axis_position(time_axis: &[(u64, f64)], time: u64) -> f64 {
+    let index = time_axis.partition_point(|&(axis_time, _)| axis_time < time);
+    match (
+        index.checked_sub(1).map(|i| time_axis[i]),
+        time_axis.get(index),
+    ) {
+        (_, Some(&(next_time, next))) if next_time == time => next,
+        (Some((previous_time, previous)), Some(&(next_time, next))) => {
+            let ratio = (time - previous_time) as f64 / (next_time - previous_time) as f64;
+            previous + ratio * (next - previous)
+        }
+        (Some((_, previous)), None) => previous,
+        (None, Some(&(_, next))) => next,
+        (None, None) => 0.,
+    }
+}
+
+impl OgImageGenerator {
+    /// Returns the series of the graph of market cards, prepared for the
+    /// template with [`ChartOptions::series()`]. Other cards have no series.
+    pub(crate) fn card_series<'a>(&self, card: &OgImageCard<'a>) -> Vec<ChartSeries<'a>> {
+        match card {
+            OgImageCard::Market(data) => self.chart.series(data.graph, &data.kind),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the serialized `chart.json` input of the template, if the
+    /// card has a chart.
+    pub(crate) fn chart_json(
+        &self,
+        card: &OgImageCard<'_>,
+    ) -> Result<Option<Vec<u8>>, OgImageError> {
+        let OgImageCard::Market(data) = card else {
+            return Ok(None);
+        };
+        let chart = self.chart.input(data.graph, &data.kind);
+        let json = serde_json::to_vec(&chart).map_err(OgImageError::JsonSerializationError)?;
+        Ok(Some(json))
+    }
+}
+
+/// Returns the card with the graph of market cards replaced by the prepared
+/// series, see [`OgImageGenerator::card_series()`].
+pub(crate) fn with_series<'a>(
+    card: OgImageCard<'a>,
+    graph: &'a [OgImageGraphData<'a>],
+) -> OgImageCard<'a> {
+    match card {
+        OgImageCard::Market(data) => OgImageCard::Market(OgImageData { graph, ..data }),
+        card => card,
+    }
+}
+
+/// Chart configuration passed to the template as the `chart` input.
+#[derive(Debug, Serialize)]
+pub(crate) struct ChartInput {
+    /// Range of the value axis
+    value_axis: ValueAxis,
+    /// Values at which gridlines are drawn
+    gridlines: Vec<f64>,
+    /// Timestamps of the data points and their positions on the time axis
+    time_axis: Vec<(u64, f64)>,
+    /// Date labels below the chart, ordered by time
+    time_labels: Vec<TimeLabel>,
+    /// Name of the [`LineStyle`] of the series
+    line_style: &'static str,
+    /// Name of the [`Markers`] policy of the series
+    markers: &'static str,
+    /// Formatted current value of every series, in the order of the graph
+    values: Vec<Option<String>>,
+    /// Current value shown prominently on the card, only for scalar markets
+    headline: Option<String>,
+}
+
+/// Range of the value axis of the chart.
+#[derive(Debug, Serialize)]
+struct ValueAxis {
+    min: f64,
+    max: f64,
+}
+
+/// A date label below the chart.
+#[derive(Debug, Serialize)]
+struct TimeLabel {
+    /// Unix timestamp of the label
+    time: u64,
+    /// Position of the label on the time axis
+    x: f64,
+    /// Formatted date
+    label: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OgImageDataPoint, OgImageScalarData};
+
+    fn graph_input(options: ChartOptions, times: &[u64]) -> serde_json::Value {
+        let data = times
+            .iter()
+            .map(|&time| OgImageDataPoint { time, value: 50. })
+            .collect::<Vec<_>>();
+        let graph = [OgImageGraphData {
+            outcome: "Yes",
+            color: "#00f29c",
+            data: &data,
+        }];
+
+        let kind = OgImageMarketKind::Probability;
+        serde_json::to_value(options.input(&graph, &kind)).unwrap()
+    }
+
+    #[test]
+    fn test_chart_input() {
+        // 2025-01-01T00:00:00Z to 2025-03-02T00:00:00Z
+        let times = [1735689600, 1740873600];
+
+        let input = graph_input(ChartOptions::default(), &times);
+        assert_eq!(
+            input,
+            serde_json::json!({
+                "value_axis": { "min": 0.0, "max": 100.0 },
+                "gridlines": [],
+                "time_axis": [[1735689600, 0.0], [1740873600, 1440.0]],
+                "time_labels": [],
+                "line_style": "straight",
+                "markers": "all",
+                "values": ["50%"],
+                "headline": null,
+            })
+        );
+
+        let options = ChartOptions::default()
+            .with_time_labels(TimeLabels::StartMiddleEnd)
+            .with_gridlines(true);
+        let input = graph_input(options, &times);
+        assert_eq!(input["gridlines"], serde_json::json!([25.0, 50.0, 75.0]));
+        assert_eq!(
+            input["time_labels"],
+            serde_json::json!([
+                { "time": 1735689600, "x": 0.0, "label": "1 Jan" },
+                { "time": 1738281600, "x": 720.0, "label": "31 Jan" },
+                { "time": 1740873600, "x": 1440.0, "label": "2 Mar" },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_fractional_probabilities() {
+        let series = |value| [OgImageDataPoint { time: 0, value }];
+        let (a, b, c) = (series(0.4), series(12.25), series(99.6));
+        let graph = [("A", &a), ("B", &b), ("C", &c)].map(|(outcome, data)| OgImageGraphData {
+            outcome,
+            color: "#00f29c",
+            data,
+        });
+        let kind = OgImageMarketKind::Probability;
+
+        let input = ChartOptions::default().input(&graph, &kind);
+        let input = serde_json::to_value(input).unwrap();
+        assert_eq!(input["values"], serde_json::json!(["<1%", "12%", ">99%"]));
+
+        let input = ChartOptions::default()
+            .with_precision(1)
+            .input(&graph, &kind);
+        let input = serde_json::to_value(input).unwrap();
+        assert_eq!(
+            input["values"],
+            serde_json::json!(["0.4%", "12.2%", "99.6%"])
+        );
+    }
+
+    #[test]
+    fn test_scalar_chart_input() {
+        let data = [
+            OgImageDataPoint {
+                time: 1735689600,
+                value: 64250.,
+            },
+            OgImageDataPoint {
+                time: 1735693200,
+                value: 61800.,
+            },
+        ];
+        let graph = [OgImageGraphData {
+            outcome: "BTC",
+            color: "#f7931a",
+            data: &data,
+        }];
+        let scalar = OgImageScalarData::new(50000., 80000., "USD");
+        let kind = OgImageMarketKind::Scalar(scalar);
+
+        let options = ChartOptions::default().with_gridlines(true);
+        let input = serde_json::to_value(options.input(&graph, &kind)).unwrap();
+        assert_eq!(
+            input["value_axis"],
+            serde_json::json!({ "min": 50000.0, "max": 80000.0 })
+        );
+        assert_eq!(
+            input["gridlines"],
+            serde_json::json!([57500.0, 65000.0, 72500.0])
+        );
+        assert_eq!(input["values"], serde_json::json!(["61,800 USD"]));
+        assert_eq!(input["headline"], "61,800 USD");
+    }
+
+    #[test]
+    fn test_time_label_formats() {
+        let options = ChartOptions::default()
+            .with_time_labels(TimeLabels::StartEnd)
+            .with_timezone(chrono_tz::Europe::Berlin)
+            .with_locale(Locale::de_DE);
+
+        // Less than a day shows the time of day in the configured timezone
+        let input = graph_input(options, &[1735689600, 1735725600]);
+        let labels = &input["time_labels"];
+        assert_eq!(labels[0]["label"], "01:00");
+        assert_eq!(labels[1]["label"], "11:00");
+
+        // More than a year shows the localized month and year
+        let input = graph_input(options, &[1735689600, 1772323200]);
+        let labels = &input["time_labels"];
+        assert_eq!(labels[0]["label"], "Jan 2025");
+        assert_eq!(labels[1]["label"], "Mär 2026");
+
+        // An empty graph has no labels
+        let input = graph_input(options, &[]);
+        assert_eq!(input["time_labels"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_time_labels_roundtrip() {
+        let all = [
+            TimeLabels::None,
+            TimeLabels::StartEnd,
+            TimeLabels::StartMiddleEnd,
+        ];
+        for time_labels in all {
+            assert_eq!(time_labels.as_str().parse(), Ok(time_labels));
+        }
+
+        assert!("middle".parse::<TimeLabels>().is_err());
+    }
+
+    #[test]
+    fn test_line_style_and_markers() {
+        let options = ChartOptions::default()
+            .with_line_style(LineStyle::Step)
+            .with_markers(Markers::Last);
+        let input = graph_input(options, &[1735689600, 1740873600]);
+        assert_eq!(input["line_style"], "step");
+        assert_eq!(input["markers"], "last");
+
+        for line_style in [LineStyle::Straight, LineStyle::Step] {
+            assert_eq!(line_style.as_str().parse(), Ok(line_style));
+        }
+        for markers in [Markers::None, Markers::Last, Markers::All] {
+            assert_eq!(markers.as_str().parse(), Ok(markers));
+        }
+
+        assert!("curved".parse::<LineStyle>().is_err());
+        assert!("first".parse::<Markers>().is_err());
+    }
+
+    #[test]
+    fn test_time_scales() {
+        // Four points an hour apart, a week of inactivity and a final point
+        let hour = 3600;
+        let times = [0, hour, 2 * hour, 3 * hour, 3 * hour + 7 * 24 * hour];
+
+        let axis = |time_scale| {
+            let options = ChartOptions::default()
+                .with_time_scale(time_scale)
+                .with_time_labels(TimeLabels::StartMiddleEnd);
+            let input = graph_input(options, &times);
+            let positions = input["time_axis"].as_array().unwrap().iter();
+            let positions = positions.map(|pair| pair[1].as_f64().unwrap());
+            let labels = input["time_labels"].as_array().unwrap().iter();
+            let labels = labels.map(|label| label["x"].as_f64().unwrap());
+            (positions.collect::<Vec<_>>(), labels.collect::<Vec<_>>())
+        };
+
+        let (positions, labels) = axis(TimeScale::Real);
+        assert_eq!(positions, [0., 1., 2., 3., 171.]);
+        assert_eq!(labels, [0., 85.5, 171.]);
+
+        let (positions, labels) = axis(TimeScale::Compressed { max_gap: 2 * hour });
+        assert_eq!(positions, [0., 1., 2., 3., 5.]);
+        assert_eq!(labels, [0., 3. + 82.5 / 168. * 2., 5.]);
+
+        let (positions, labels) = axis(TimeScale::Ordinal);
+        assert_eq!(positions, [0., 1., 2., 3., 4.]);
+        assert_eq!(labels, [0., 3. + 82.5 / 168., 4.]);
+    }
+
+    #[test]
+    fn test_time_scale_roundtrip() {
+        let all = [
+            TimeScale::Real,
+            TimeScale::Compressed { max_gap: 3600 },
+            TimeScale::Ordinal,
+        ];
+        for time_scale in all {
+            assert_eq!(time_scale.to_string().parse(), Ok(time_scale));
+        }
+
+        assert!("compressed".parse::<TimeScale>().is_err());
+        assert!("compressed:1h".parse::<TimeScale>().is_err());
+        assert!("linear".parse::<TimeScale>().is_err());
+    }
+
+    #[test]
+    fn test_chart_series() {
+        let points = |values: &[u32]| {
+            let points = values.iter().enumerate();
+            let points = points.map(|(time, &value)| OgImageDataPoint {
+                time: time as u64 * 10,
+                value: f64::from(value),
+            });
+            points.collect::<Vec<_>>()
+        };
+        let (a, b, c) = (points(&[30, 40]), points(&[20, 25]), points(&[50, 35]));
+        let (d, e) = (points(&[0]), points(&[5, 4, 3]));
+        let graph = [
+            OgImageGraphData {
+                outcome: "A",
+                color: "#123456",
+                data: &a,
+            },
+            OgImageGraphData {
+                outcome: "B",
+                color: "",
+                data: &b,
+            },
+            OgImageGraphData {
+                outcome: "C",
+                color: "red",
+                data: &c,
+            },
+            OgImageGraphData {
+                outcome: "D",
+                color: "#ggg",
+                data: &d,
+            },
+            OgImageGraphData {
+                outcome: "E",
+                color: "#abc",
+                data: &e,
+            },
+        ];
+
+        // Outcomes are ordered by their last value, invalid colors replaced
+        let series = ChartOptions::default().series(&graph, &OgImageMarketKind::Probability);
+        let outcomes = series.iter().map(|series| (series.outcome, series.color));
+        assert_eq!(
+            outcomes.collect::<Vec<_>>(),
+            [
+                ("A", "#123456"),
+                ("C", PALETTE[1]),
+                ("B", PALETTE[0]),
+                ("E", "#abc"),
+                ("D", PALETTE[2]),
+            ]
+        );
+
+        // Palette colors used by another outcome are skipped
+        let mut colored = graph.clone();
+        colored[0].color = "#00f29c";
+        colored[2].color = "#8a9a94";
+        let series = ChartOptions::default().series(&colored, &OgImageMarketKind::Probability);
+        let colors = series.iter().map(|series| series.color);
+        assert_eq!(
+            colors.collect::<Vec<_>>(),
+            ["#00f29c", "#8a9a94", PALETTE[1], "#abc", PALETTE[2]]
+        );
+
+        // The "Other" series is not gray if a shown outcome is
+        let options = ChartOptions::default().with_max_outcomes(3);
+        let series = options.series(&colored, &OgImageMarketKind::Probability);
+        let colors = series.iter().map(|series| series.color);
+        assert_eq!(
+            colors.collect::<Vec<_>>(),
+            ["#00f29c", "#8a9a94", PALETTE[1], PALETTE[2]]
+        );
+
+        // The least likely outcomes are combined
+        let options = ChartOptions::default().with_max_outcomes(2);
+        let series = options.series(&graph, &OgImageMarketKind::Probability);
+        let outcomes = series.iter().map(|series| series.outcome);
+        assert_eq!(outcomes.collect::<Vec<_>>(), ["A", "C", "Other"]);
+        assert_eq!(series[2].color, OTHER_COLOR);
+        assert_eq!(series[2].data, points(&[25, 29, 28]));
+
+        // A single remaining outcome is not combined
+        let series = ChartOptions::default()
+            .with_max_outcomes(4)
+            .series(&graph, &OgImageMarketKind::Probability);
+        assert_eq!(series.len(), 5);
+
+        // The values of scalar markets are not combined
+        let kind = OgImageMarketKind::Scalar(OgImageScalarData::new(0., 100., "USD"));
+        let series = options.series(&graph, &kind);
+        let outcomes = series.iter().map(|series| series.outcome);
+        assert_eq!(outcomes.collect::<Vec<_>>(), ["A", "C"]);
+    }
+
+    #[test]
+    fn test_downsample_graph() {
+        let data = (0..2000)
+            .rev()
+            .map(|time| OgImageDataPoint { time, value: 50. })
+            .collect::<Vec<_>>();
+        let graph = [OgImageGraphData {
+            outcome: "Yes",
+            color: "#00f29c",
+            data: &data,
+        }];
+
+        let options = ChartOptions::default().with_max_points(100);
+        let series = options.series(&graph, &OgImageMarketKind::Probability);
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].data.len(), 100);
+        assert_eq!(series[0].data.first().unwrap().time, 0);
+        assert_eq!(series[0].data.last().unwrap().time, 1999);
+
+        let options = ChartOptions::default();
+        assert_eq!(
+            options.series(&graph, &OgImageMarketKind::Probability)[0]
+                .data
+                .len(),
+            DEFAULT_MAX_POINTS
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Module for parsing Typst compiler diagnostics.
+//!
+//! The Typst CLI is invoked with `--diagnostic-format short`, which prints one
+//! diagnostic per line in the form `file:line:column: severity: message`.
+//! Diagnostics without a source location are printed as `severity: message`.
+
+use serde::Serialize;
+use std::fmt;
+
+/// Severity of a Typst diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    /// A fatal error that caused the compilation to fail
+    Error,
+    /// A warning that did not prevent the compilation from succeeding
+    Warning,
+    /// Additional context for a previous diagnostic (e.g. a call trace)
+    Help,
+    /// An informational note attached to a previous diagnostic
+    Note,
+}
+
+impl DiagnosticSeverity {
+    const ALL: [Self; 4] = [Self::Error, Self::Warning, Self::Help, Self::Note];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Help => "help",
+            Self::Note => "note",
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single diagnostic message emitted by the Typst compiler.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TypstDiagnostic {
+    /// Severity of the diagnostic
+    pub severity: DiagnosticSeverity,
+    /// Human-readable diagnostic message
+    pub message: String,
+    /// Source file the diagnostic refers to, relative to the project root
+    /// or as a package path (e.g. `@preview/cetz:0.4.0/src/lib.typ`)
+    pub file: Option<String>,
+    /// One-based line number within `file`
+    pub line: Option<u32>,
+    /// One-based column number within `file`
+    pub column: Option<u32>,
+}
+
+impl fmt::Display for TypstDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}:")?;
+            if let (Some(line), Some(column)) = (self.line, self.column) {
+                write!(f, "{line}:{column}:")?;
+            }
+            f.write_str(" ")?;
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Parses the output of the Typst CLI in `short` diagnostic format.
+///
+/// Lines that do not start a new diagnostic are treated as a continuation
+/// of the previous diagnostic's message. Output that precedes the first
+/// diagnostic is ignored.
+pub fn parse_diagnostics(output: &str) -> Vec<TypstDiagnostic> {
+    let mut diagnostics: Vec<TypstDiagnostic> = Vec::new();
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(diagnostic) = parse_line(line) {
+            diagnostics.push(diagnostic);
+        } else if let Some(previous) = diagnostics.last_mut() {
+            previous.message.push('\n');
+            previous.message.push_str(line.trim_end());
+        }
+    }
+
+    diagnostics
+}
+
+/// Parses a single line that starts a new diagnostic.
+fn parse_line(line: &str) -> Option<TypstDiagnostic> {
+    // Messages may contain the marker of another severity, e.g. a warning
+    // about `: error: `, so the earliest marker of the line is used
+    let (_, severity, location, message) = DiagnosticSeverity::ALL
+        .into_iter()
+        .filter_map(|severity| find_severity(line, severity))
+        .min_by_key(|(position, ..)| *position)?;
+
+    // Diagnostics without a source location, e.g. `error: file not found`
+    let Some(location) = location else {
+        return Some(TypstDiagnostic {
+            severity,
+            message: message.trim_end().to_string(),
+            file: None,
+            line: None,
+            column: None,
+        });
+    };
+
+    // Diagnostics with a source location, e.g. `main.typ:1:2: error: oops`.
+    // File names may contain colons (e.g. package specs), so split from the right
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next().and_then(|s| s.parse().ok());
+    let line_number = parts.next().and_then(|s| s.parse().ok());
+
+    let (file, line_number, column) = match (parts.next(), line_number, column) {
+        (Some(file), Some(line_number), Some(column)) => (file, Some(line_number), Some(column)),
+        _ => (location, None, None),
+    };
+
+    Some(TypstDiagnostic {
+        severity,
+        message: message.trim_end().to_string(),
+        file: Some(file.to_string()),
+        line: line_number,
+        column,
+    })
+}
+
+/// Finds the marker of the given severity in a line, returning its position,
+/// the source location before it, if any, and the message after it.
+fn find_severity(
+    line: &str,
+    severity: DiagnosticSeverity,
+) -> Option<(usize, DiagnosticSeverity, Option<&str>, &str)> {
+    let prefix = format!("{severity}: ");
+    if let Some(message) = line.strip_prefix(&prefix) {
+        return Some((0, severity, None, message));
+    }
+
+    let separator = format!(": {prefix}");
+    let position = line.find(&separator)?;
+    let message = &line[position + separator.len()..];
+    Some((position, severity, Some(&line[..position]), message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diagnostics_with_location() {
+        let output = "og-image.typ:12:5: error: unknown variable: foo\n";
+        let diagnostics = parse_diagnostics(output);
+
+        assert_eq!(
+            diagnostics,
+            vec![TypstDiagnostic {
+                severity: DiagnosticSeverity::Error,
+                message: "unknown variable: foo".to_string(),
+                file: Some("og-image.typ".to_string()),
+                line: Some(12),
+                column: Some(5),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_diagnostics_without_location() {
+        let output = "error: file not found (searched at /tmp/data.json)";
+        let diagnostics = parse_diagnostics(output);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+        assert_eq!(
+            diagnostics[0].message,
+            "file not found (searched at /tmp/data.json)"
+        );
+        assert_eq!(diagnostics[0].file, None);
+        assert_eq!(diagnostics[0].line, None);
+        assert_eq!(diagnostics[0].column, None);
+    }
+
+    #[test]
+    fn test_parse_diagnostics_package_path() {
+        let output = "@preview/cetz:0.4.0/src/lib.typ:3:14: help: error occurred in this call";
+        let diagnostics = parse_diagnostics(output);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Help);
+        assert_eq!(
+            diagnostics[0].file.as_deref(),
+            Some("@preview/cetz:0.4.0/src/lib.typ")
+        );
+        assert_eq!(diagnostics[0].line, Some(3));
+        assert_eq!(diagnostics[0].column, Some(14));
+    }
+
+    #[test]
+    fn test_parse_diagnostics_severity_in_message() {
+        let output = "\
+og-image.typ:12:5: warning: label `: error: ` does not exist
+warning: unknown variable: note: x
+";
+        let diagnostics = parse_diagnostics(output);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostics[0].file.as_deref(), Some("og-image.typ"));
+        assert_eq!(diagnostics[0].line, Some(12));
+        assert_eq!(diagnostics[0].column, Some(5));
+        assert_eq!(diagnostics[0].message, "label `: error: ` does not exist");
+        assert_eq!(diagnostics[1].severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostics[1].file, None);
+        assert_eq!(diagnostics[1].message, "unknown variable: note: x");
+    }
+
+    #[test]
+    fn test_parse_diagnostics_mixed() {
+        let output = "\
+og-image.typ:220:1: warning: unknown font family: ibm plex sans
+og-image.typ:302:25: error: array is empty
+  continued message
+og-image.typ:250:9: help: error occurred in this function call
+";
+        let diagnostics = parse_diagnostics(output);
+
+        let severities = diagnostics.iter().map(|d| d.severity).collect::<Vec<_>>();
+        assert_eq!(
+            severities,
+            vec![
+                DiagnosticSeverity::Warning,
+                DiagnosticSeverity::Error,
+                DiagnosticSeverity::Help
+            ]
+        );
+        assert_eq!(
+            diagnostics[1].message,
+            "array is empty\n  continued message"
+        );
+    }
+
+    #[test]
+    fn test_parse_diagnostics_ignores_unrelated_output() {
+        assert_eq!(parse_diagnostics(""), vec![]);
+        assert_eq!(parse_diagnostics("compiling ...\n\n"), vec![]);
+    }
+
+    #[test]
+    fn test_display_diagnostic() {
+        let diagnostic = TypstDiagnostic {
+            severity: DiagnosticSeverity::Warning,
+            message: "unknown font family: ibm plex sans".to_string(),
+            file: Some("og-image.typ".to_string()),
+            line: Some(220),
+            column: Some(1),
+        };
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "og-image.typ:220:1: warning: unknown font family: ibm plex sans"
+        );
+    }
+}
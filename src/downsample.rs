@@ -0,0 +1,173 @@
+//! Downsampling of chart series before they are passed to the template.
+//!
+//! Markets can have tens of thousands of data points, which are slow to plot
+//! and make the template input unnecessarily large. Both algorithms reduce a
+//! series to a maximum number of points while keeping its overall shape, and
+//! always keep the first and the last data point, so the current value of
+//! the series is never lost.
+
+use crate::{Downsampling, OgImageDataPoint};
+
+/// Reduces the data points, ordered by time, to at most `max_points`.
+pub(crate) fn downsample(
+    data: &[OgImageDataPoint],
+    max_points: usize,
+    downsampling: Downsampling,
+) -> Vec<OgImageDataPoint> {
+    if data.len() <= max_points {
+        return data.to_vec();
+    }
+
+    match downsampling {
+        Downsampling::Lttb => lttb(data, max_points),
+        Downsampling::MinMax => min_max(data, max_points),
+    }
+}
+
+/// Largest-Triangle-Three-Buckets downsampling.
+///
+/// The points between the first and the last one are split into
+/// `max_points - 2` buckets. From each bucket, the point forming the largest
+/// triangle with the previously selected point and the average of the next
+/// bucket is selected, which preserves peaks and trend changes.
+fn lttb(data: &[OgImageDataPoint], max_points: usize) -> Vec<OgImageDataPoint> {
+    let (first, last) = (&data[0], &data[data.len() - 1]);
+    let buckets = max_points.saturating_sub(2).max(1);
+    let bucket_size = (data.len() - 2) as f64 / buckets as f64;
+    let bucket = |index: usize| {
+        let start = (index as f64 * bucket_size) as usize + 1;
+        let end = ((index + 1) as f64 * bucket_size) as usize + 1;
+        start..end.min(data.len() - 1)
+    };
+
+    // Times are made relative to the first point to keep the precision of
+    // the area calculation
+    let point = |point: &OgImageDataPoint| ((point.time - first.time) as f64, point.value);
+
+    let mut sampled = Vec::with_capacity(max_points);
+    sampled.push(first.clone());
+
+    let mut selected = point(first);
+    for index in 0..buckets {
+        // Average of the next bucket, or the last point for the final bucket
+        let next = &data[bucket(index + 1)];
+        let (next_x, next_y) = match next.len() {
+            0 => point(last),
+            len => {
+                let (sum_x, sum_y) = next
+                    .iter()
+                    .map(point)
+                    .fold((0., 0.), |(sx, sy), (x, y)| (sx + x, sy + y));
+                (sum_x / len as f64, sum_y / len as f64)
+            }
+        };
+
+        let (selected_x, selected_y) = selected;
+        let area = |candidate: &OgImageDataPoint| {
+            let (x, y) = point(candidate);
+            ((selected_x - next_x) * (y - selected_y) - (selected_x - x) * (next_y - selected_y))
+                .abs()
+        };
+
+        let candidates = &data[bucket(index)];
+        let largest = candidates.iter().max_by(|a, b| area(a).total_cmp(&area(b)));
+
+        if let Some(largest) = largest {
+            selected = point(largest);
+            sampled.push(largest.clone());
+        }
+    }
+
+    sampled.push(last.clone());
+    sampled
+}
+
+/// Min/max bucket downsampling.
+///
+/// The points between the first and the last one are split into
+/// `(max_points - 2) / 2` buckets, and the points with the lowest and the
+/// highest value of each bucket are kept in their original order. This
+/// preserves the full value range of the series.
+fn min_max(data: &[OgImageDataPoint], max_points: usize) -> Vec<OgImageDataPoint> {
+    let (first, last) = (&data[0], &data[data.len() - 1]);
+    let inner = &data[1..data.len() - 1];
+    let buckets = (max_points.saturating_sub(2) / 2).max(1);
+    let bucket_size = inner.len().div_ceil(buckets);
+
+    let mut sampled = Vec::with_capacity(max_points);
+    sampled.push(first.clone());
+
+    for bucket in inner.chunks(bucket_size) {
+        let min = bucket
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.value.total_cmp(&b.value));
+        let max = bucket
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.value.total_cmp(&b.value));
+        let (Some((min_index, min)), Some((max_index, max))) = (min, max) else {
+            continue;
+        };
+
+        match min_index.cmp(&max_index) {
+            std::cmp::Ordering::Less => sampled.extend([min.clone(), max.clone()]),
+            std::cmp::Ordering::Greater => sampled.extend([max.clone(), min.clone()]),
+            std::cmp::Ordering::Equal => sampled.push(min.clone()),
+        }
+    }
+
+    sampled.push(last.clone());
+    sampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A noisy series with a single spike at 5000 and a final value of 42.
+    fn test_series(len: u64) -> Vec<OgImageDataPoint> {
+        (0..len)
+            .map(|time| {
+                let value = match time {
+                    5000 => 100.,
+                    _ if time == len - 1 => 42.,
+                    _ => 40. + (time * 7919 % 11) as f64,
+                };
+                OgImageDataPoint { time, value }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_downsample_short_series() {
+        let data = test_series(100);
+        assert_eq!(downsample(&data, 500, Downsampling::Lttb), data);
+        assert_eq!(downsample(&data, 500, Downsampling::MinMax), data);
+    }
+
+    #[test]
+    fn test_downsample() {
+        let data = test_series(20_000);
+
+        for downsampling in [Downsampling::Lttb, Downsampling::MinMax] {
+            let sampled = downsample(&data, 500, downsampling);
+            assert!(sampled.len() <= 500, "{downsampling:?}: {}", sampled.len());
+            assert!(sampled.len() >= 400, "{downsampling:?}: {}", sampled.len());
+
+            // The first and the final value are kept
+            assert_eq!(sampled.first(), data.first());
+            assert_eq!(sampled.last(), data.last());
+
+            // The points stay ordered by time
+            assert!(sampled.windows(2).all(|pair| pair[0].time < pair[1].time));
+
+            // The spike is kept
+            let spike = OgImageDataPoint {
+                time: 5000,
+                value: 100.,
+            };
+            assert!(sampled.contains(&spike), "{downsampling:?}");
+        }
+    }
+}
@@ -1,6 +1,9 @@
 //! Error types for the crates_io_og_image crate.
 
+use crate::diagnostics::TypstDiagnostic;
+use crate::validation::FieldError;
 use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors that can occur when generating OpenGraph images.
@@ -40,8 +43,14 @@ pub enum OgImageError {
         stderr: String,
         stdout: String,
         exit_code: Option<i32>,
+        /// Diagnostics parsed from the compiler output
+        diagnostics: Vec<TypstDiagnostic>,
     },
 
+    /// The Typst compilation did not finish within the render timeout.
+    #[error("Typst compilation timed out after {0:?}")]
+    TypstTimeout(Duration),
+
     /// I/O error.
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
@@ -50,7 +59,53 @@ pub enum OgImageError {
     #[error("Failed to create temporary file: {0}")]
     TempFileError(std::io::Error),
 
+    /// Failed to read or write an object in a filesystem image store.
+    #[error("Failed to access image store object '{key}': {source}")]
+    StoreIoError {
+        key: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Failed to send a request to a remote image store.
+    #[error("Failed to send image store request for '{key}': {source}")]
+    StoreRequestError {
+        key: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// A remote image store returned an unexpected response.
+    #[error("Image store returned status {status} for '{key}': {body}")]
+    StoreResponseError {
+        key: String,
+        status: reqwest::StatusCode,
+        body: String,
+    },
+
+    /// Failed to load a template from a directory.
+    #[error("Failed to load template from {path:?}: {source}")]
+    TemplateLoadError {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The image data contains invalid fields, see
+    /// [`OgImageCard::validate()`](crate::OgImageCard::validate).
+    #[error("Invalid input data: {}", format_field_errors(.0))]
+    InvalidInput(Vec<FieldError>),
+
+    /// The image data lacks a field required by the template.
+    #[error("Template '{template}' requires the missing input field '{field}'")]
+    MissingTemplateInput { template: String, field: String },
+
     /// Temporary directory creation error.
     #[error("Failed to create temporary directory: {0}")]
     TempDirError(std::io::Error),
 }
+
+fn format_field_errors(errors: &[FieldError]) -> String {
+    let errors = errors.iter().map(FieldError::to_string);
+    errors.collect::<Vec<_>>().join("; ")
+}
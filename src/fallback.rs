@@ -0,0 +1,26 @@
+//! Fallback images returned when OpenGraph image generation fails.
+
+use std::borrow::Cow;
+
+/// Pre-rendered branded card bundled with the crate.
+const BUNDLED_FALLBACK_PNG: &[u8] = include_bytes!("../template/assets/fallback.png");
+
+/// Image returned by [`OgImageGenerator::generate()`](crate::OgImageGenerator::generate)
+/// when rendering fails and fallback mode is enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FallbackImage {
+    /// The static branded card bundled with this crate
+    Bundled,
+    /// A custom pre-rendered PNG image
+    Custom(Cow<'static, [u8]>),
+}
+
+impl FallbackImage {
+    /// Returns the PNG bytes of the fallback image.
+    pub fn bytes(&self) -> &[u8] {
+        match self {
+            Self::Bundled => BUNDLED_FALLBACK_PNG,
+            Self::Custom(bytes) => bytes,
+        }
+    }
+}
@@ -3,8 +3,17 @@
 //! This module contains utility functions for formatting numbers in various ways,
 //! such as human-readable byte sizes.
 
+use chrono::{DateTime, Datelike, Locale, TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::Serializer;
 
+/// Maximum number of decimal places of formatted values.
+pub(crate) const MAX_DECIMALS: u8 = 4;
+
+const SECONDS_PER_MINUTE: u64 = 60;
+const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE;
+const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+
 /// Formats a byte size value into a human-readable string.
 ///
 /// The function follows these rules:
@@ -115,6 +124,176 @@ pub fn serialize_optional_number<S: Serializer>(
     }
 }
 
+/// Formats a number with "," as thousands separator and the given number of
+/// decimal places, e.g. "-1,234.5".
+///
+/// Numbers that are rounded to zero are shown without a sign.
+///
+/// # Arguments
+///
+/// * `number` - The number to format
+/// * `decimals` - The number of decimal places
+///
+/// # Returns
+///
+/// A formatted string with the whole digits grouped in thousands
+pub fn format_decimal(number: f64, decimals: u8) -> String {
+    let decimals = usize::from(decimals);
+    let formatted = format!("{:.decimals$}", number.abs());
+    let (whole, fraction) = match formatted.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (formatted.as_str(), None),
+    };
+
+    let is_zero = formatted.bytes().all(|byte| matches!(byte, b'0' | b'.'));
+    let sign = if number < 0. && !is_zero { "-" } else { "" };
+    match fraction {
+        Some(fraction) => format!("{sign}{}.{fraction}", group_thousands(whole)),
+        None => format!("{sign}{}", group_thousands(whole)),
+    }
+}
+
+/// Inserts "," between every group of three digits, counted from the end.
+fn group_thousands(digits: &str) -> String {
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Formats a probability in percent with the given number of decimal places.
+///
+/// Probabilities that would be rounded to 0% or 100%, without being exactly
+/// 0% or 100%, are shown as "<1%" or ">99%", with the smallest step of the
+/// precision, e.g. "<0.1%" for one decimal place. Values halfway between two
+/// steps are rounded to even, so 0.5% is shown as "<1%" and 99.5% as ">99%".
+///
+/// # Arguments
+///
+/// * `value` - The probability in percent
+/// * `precision` - The number of decimal places
+///
+/// # Returns
+///
+/// A formatted string like "40%", "12.5%" or "<1%"
+pub fn format_percent(value: f64, precision: u8) -> String {
+    let precision = usize::from(precision);
+    let step = 10f64.powi(-(precision as i32));
+
+    if value > 0. && value <= step / 2. {
+        format!("<{step:.precision$}%")
+    } else if value < 100. && value >= 100. - step / 2. {
+        format!(">{:.precision$}%", 100. - step)
+    } else {
+        format!("{value:.precision$}%")
+    }
+}
+
+/// Formats an amount of cents as a signed dollar amount, using the suffixes
+/// of [`format_number()`] for the whole dollars.
+///
+/// Positive amounts are prefixed with "+", negative amounts with "-", and
+/// amounts below one dollar are shown as "$0".
+///
+/// # Arguments
+///
+/// * `cents` - The amount in cents to format
+///
+/// # Returns
+///
+/// A formatted string like "+$1.5K" or "-$350"
+pub fn format_signed_dollars(cents: i64) -> String {
+    let dollars = u32::try_from(cents.unsigned_abs() / 100).unwrap_or(u32::MAX);
+    let sign = match cents {
+        _ if dollars == 0 => "",
+        ..0 => "-",
+        _ => "+",
+    };
+
+    format!("{sign}${}", format_number(dollars))
+}
+
+pub fn serialize_signed_dollars<S: Serializer>(
+    cents: &i64,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_signed_dollars(*cents))
+}
+
+/// Formats a duration in seconds as the largest whole unit, e.g. "3d".
+///
+/// Durations are rounded down, so 3 days and 20 hours are shown as "3d", and
+/// durations below one minute are shown as "<1m".
+///
+/// # Arguments
+///
+/// * `seconds` - The duration in seconds
+///
+/// # Returns
+///
+/// A formatted string like "3d", "5h", "12m" or "<1m"
+pub fn format_duration(seconds: u64) -> String {
+    match seconds {
+        ..SECONDS_PER_MINUTE => "<1m".to_string(),
+        SECONDS_PER_MINUTE..SECONDS_PER_HOUR => format!("{}m", seconds / SECONDS_PER_MINUTE),
+        SECONDS_PER_HOUR..SECONDS_PER_DAY => format!("{}h", seconds / SECONDS_PER_HOUR),
+        _ => format!("{}d", seconds / SECONDS_PER_DAY),
+    }
+}
+
+/// Formats the closing or resolution time of a market relative to `now`.
+///
+/// The function follows these rules:
+/// - Resolved markets show the resolution date, e.g. "Resolved Apr 21"
+/// - Markets past their closing time show the closing date, e.g. "Closed Apr 21"
+/// - Open markets show the time until they close, e.g. "Ends in 3d"
+/// - Dates in another year than `now` include the year, e.g. "Closed Apr 21, 2024"
+///
+/// The month names are localized, the surrounding words are always English.
+///
+/// # Arguments
+///
+/// * `closes_at` - Unix timestamp at which the market closes, if any
+/// * `resolved_at` - Unix timestamp at which the market was resolved, if any
+/// * `now` - Unix timestamp of the current time
+/// * `timezone` - Timezone of the formatted dates
+/// * `locale` - Locale of the formatted dates
+///
+/// # Returns
+///
+/// A formatted string, or `None` if neither timestamp is set
+pub fn format_market_timing(
+    closes_at: Option<u64>,
+    resolved_at: Option<u64>,
+    now: u64,
+    timezone: Tz,
+    locale: Locale,
+) -> Option<String> {
+    let date = |time: u64| {
+        let to_datetime = |time: u64| {
+            let datetime = DateTime::<Utc>::from_timestamp(time.try_into().ok()?, 0)?;
+            Some(timezone.from_utc_datetime(&datetime.naive_utc()))
+        };
+        let (datetime, now) = (to_datetime(time)?, to_datetime(now)?);
+        let format = match datetime.year() == now.year() {
+            true => "%b %-d",
+            false => "%b %-d, %Y",
+        };
+        Some(datetime.format_localized(format, locale).to_string())
+    };
+
+    match (closes_at, resolved_at) {
+        (_, Some(resolved_at)) => Some(format!("Resolved {}", date(resolved_at)?)),
+        (Some(closes_at), None) if closes_at <= now => Some(format!("Closed {}", date(closes_at)?)),
+        (Some(closes_at), None) => Some(format!("Ends in {}", format_duration(closes_at - now))),
+        (None, None) => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,4 +353,117 @@ mod tests {
         assert_eq!(format_number(100000000), "100M");
         assert_eq!(format_number(1000000000), "1000M");
     }
+
+    #[test]
+    fn test_format_decimal() {
+        assert_eq!(format_decimal(0., 0), "0");
+        assert_eq!(format_decimal(999., 0), "999");
+        assert_eq!(format_decimal(1000., 0), "1,000");
+        assert_eq!(format_decimal(64250.4, 0), "64,250");
+        assert_eq!(format_decimal(f64::from(u32::MAX), 0), "4,294,967,295");
+        assert_eq!(format_decimal(1234567.891, 2), "1,234,567.89");
+        assert_eq!(format_decimal(21.5, 1), "21.5");
+        assert_eq!(format_decimal(-1234.5, 1), "-1,234.5");
+        assert_eq!(format_decimal(-0.04, 1), "0.0");
+        assert_eq!(format_decimal(-12.5, 0), "-12");
+    }
+
+    #[test]
+    fn test_format_percent() {
+        assert_eq!(format_percent(0., 0), "0%");
+        assert_eq!(format_percent(0.4, 0), "<1%");
+        assert_eq!(format_percent(0.49, 0), "<1%");
+        assert_eq!(format_percent(0.5, 0), "<1%");
+        assert_eq!(format_percent(0.6, 0), "1%");
+        assert_eq!(format_percent(1.4, 0), "1%");
+        assert_eq!(format_percent(42.5, 0), "42%");
+        assert_eq!(format_percent(98.7, 0), "99%");
+        assert_eq!(format_percent(99.4, 0), "99%");
+        assert_eq!(format_percent(99.49, 0), "99%");
+        assert_eq!(format_percent(99.5, 0), ">99%");
+        assert_eq!(format_percent(99.6, 0), ">99%");
+        assert_eq!(format_percent(100., 0), "100%");
+
+        assert_eq!(format_percent(0.4, 1), "0.4%");
+        assert_eq!(format_percent(0.04, 1), "<0.1%");
+        assert_eq!(format_percent(0.06, 1), "0.1%");
+        assert_eq!(format_percent(42.25, 2), "42.25%");
+        assert_eq!(format_percent(99.96, 1), ">99.9%");
+    }
+
+    #[test]
+    fn test_format_signed_dollars() {
+        assert_eq!(format_signed_dollars(0), "$0");
+        assert_eq!(format_signed_dollars(99), "$0");
+        assert_eq!(format_signed_dollars(-99), "$0");
+        assert_eq!(format_signed_dollars(35000), "+$350");
+        assert_eq!(format_signed_dollars(-35000), "-$350");
+        assert_eq!(format_signed_dollars(150000), "+$1.5K");
+        assert_eq!(format_signed_dollars(-250_000_000), "-$2.5M");
+        assert_eq!(format_signed_dollars(i64::MIN), "-$4295M");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "<1m");
+        assert_eq!(format_duration(59), "<1m");
+        assert_eq!(format_duration(60), "1m");
+        assert_eq!(format_duration(59 * 60 + 59), "59m");
+        assert_eq!(format_duration(60 * 60), "1h");
+        assert_eq!(format_duration(23 * 60 * 60), "23h");
+        assert_eq!(format_duration(24 * 60 * 60), "1d");
+        assert_eq!(format_duration(3 * 24 * 60 * 60 + 20 * 60 * 60), "3d");
+    }
+
+    #[test]
+    fn test_format_market_timing() {
+        // 2025-04-21 10:00:00 UTC
+        let now = 1_745_229_600;
+        let format = |closes_at, resolved_at| {
+            format_market_timing(closes_at, resolved_at, now, Tz::UTC, Locale::en_US)
+                .unwrap_or_default()
+        };
+
+        assert_eq!(format(None, None), "");
+        assert_eq!(format(Some(now + 3 * SECONDS_PER_HOUR), None), "Ends in 3h");
+        assert_eq!(format(Some(now + 3 * SECONDS_PER_DAY), None), "Ends in 3d");
+        assert_eq!(format(Some(now), None), "Closed Apr 21");
+        assert_eq!(
+            format(Some(now - 400 * SECONDS_PER_DAY), None),
+            "Closed Mar 17, 2024"
+        );
+        assert_eq!(
+            format(Some(now), Some(now + SECONDS_PER_DAY)),
+            "Resolved Apr 22"
+        );
+        assert_eq!(format(None, Some(now)), "Resolved Apr 21");
+
+        // Dates are formatted in the given timezone
+        let timing = format_market_timing(
+            Some(now - 11 * SECONDS_PER_HOUR),
+            None,
+            now,
+            Tz::Asia__Tokyo,
+            Locale::en_US,
+        );
+        assert_eq!(timing.as_deref(), Some("Closed Apr 21"));
+        let timing = format_market_timing(
+            Some(now - 11 * SECONDS_PER_HOUR),
+            None,
+            now,
+            Tz::America__New_York,
+            Locale::en_US,
+        );
+        assert_eq!(timing.as_deref(), Some("Closed Apr 20"));
+
+        // Month names are formatted in the given locale
+        let timing = format_market_timing(
+            Some(now - 400 * SECONDS_PER_DAY),
+            None,
+            now,
+            Tz::UTC,
+            Locale::de_DE,
+        );
+        assert_eq!(timing.as_deref(), Some("Closed Mär 17, 2024"));
+    }
 }
@@ -0,0 +1,61 @@
+//! Page layouts supported by the OpenGraph image template.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Page layout of a generated image.
+///
+/// The pixel dimensions given below apply to PNG images rendered at the
+/// default [scale](crate::OgImageGenerator::with_scale) of `1.0`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// 1200×630 pixels, the recommended size for OpenGraph images
+    #[default]
+    OpenGraph,
+    /// 1200×1200 pixels with a taller chart, e.g. for chat embeds
+    Square,
+}
+
+impl Layout {
+    /// Returns the name of the layout, as passed to the Typst template.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OpenGraph => "opengraph",
+            Self::Square => "square",
+        }
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "opengraph" => Ok(Self::OpenGraph),
+            "square" => Ok(Self::Square),
+            _ => Err(format!(
+                "Unknown layout '{s}', expected 'opengraph' or 'square'"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_roundtrip() {
+        for layout in [Layout::OpenGraph, Layout::Square] {
+            assert_eq!(layout.as_str().parse::<Layout>(), Ok(layout));
+        }
+
+        assert!("portrait".parse::<Layout>().is_err());
+    }
+}
@@ -1,16 +1,80 @@
 #![doc = include_str!("../README.md")]
 
+mod batch;
+mod cache;
+mod card;
+mod chart;
+mod diagnostics;
+mod downsample;
 mod env;
 mod error;
+mod fallback;
 mod formatting;
-
+mod layout;
+mod optimization;
+mod output;
+mod owned;
+mod s3;
+mod self_check;
+mod store;
+mod telemetry;
+mod template;
+mod theme;
+mod validation;
+
+pub use batch::{
+    BatchOptions, BatchRecord, BatchRecordId, BatchReportEntry, BatchStatus, BatchSummary,
+    read_completed_records,
+};
+pub use cache::{AvatarCache, FilesystemRenderCache, MemoryRenderCache, RenderCache};
+pub use card::{
+    CardKind, OgImageCard, OgImageCommentData, OgImageCommunityPageData, OgImageMarketSummary,
+    OgImageProfileData,
+};
+pub use chart::{ChartOptions, Downsampling, LineStyle, Markers, TimeLabels, TimeScale};
+pub use chrono::Locale;
+pub use chrono_tz::Tz;
+pub use diagnostics::{DiagnosticSeverity, TypstDiagnostic};
 pub use error::OgImageError;
-
+pub use fallback::FallbackImage;
+pub use layout::Layout;
+pub use optimization::{OptimizationConfig, OptimizationStats, StripMode};
+pub use output::{AvatarSkipReason, ImageFormat, OgImageOutput, OgImageTimings, SkippedAvatar};
+pub use owned::{
+    OwnedOgImageAuthorData, OwnedOgImageCommunityData, OwnedOgImageData, OwnedOgImageGraphData,
+    OwnedOgImageMarketKind, OwnedOgImageScalarData,
+};
+pub use s3::{S3Config, S3ImageStore};
+pub use self_check::{
+    BinaryCheck, BinaryStatus, FontCheck, FontStatus, REQUIRED_FONT_FAMILY,
+    SUPPORTED_OXIPNG_VERSIONS, SUPPORTED_TYPST_VERSIONS, SelfCheckReport, Version, VersionRange,
+};
+pub use store::{
+    DEFAULT_CACHE_CONTROL, FilesystemImageStore, ImageStore, PublishOptions, PublishOutcome,
+    PublishedImage,
+};
+pub use telemetry::{
+    AVATAR_DOWNLOADS_TOTAL, COMPILE_DURATION_SECONDS, GENERATIONS_TOTAL, OPTIMIZATION_SAVED_BYTES,
+    OUTPUT_SIZE_BYTES, RENDER_CACHE_LOOKUPS_TOTAL, describe_metrics,
+};
+pub use template::{InputSchema, TEMPLATE_MANIFEST, Template};
+pub use theme::Theme;
+pub use validation::FieldError;
+
+use crate::cache::AvatarImage;
+use crate::card::CardImage;
+use crate::chart::{ChartSeries, with_series};
+use crate::diagnostics::parse_diagnostics;
 use crate::env::var;
+use crate::output::ImageInfo;
+use crate::telemetry::{AvatarDownloadOutcome, CacheLookupResult, GenerationResult};
+use crate::template::{AVATAR_MAP_FILE, CHART_FILE, DATA_FILE};
 use reqwest::StatusCode;
-use serde::Serialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tempfile::NamedTempFile;
 use tokio::fs;
 use tokio::process::Command;
@@ -27,8 +91,129 @@ pub struct OgImageData<'a> {
     pub community: OgImageCommunityData<'a>,
     /// Current outcome status
     pub outcome: &'a str,
+    /// Kind of market, determining how the values of the graph are shown
+    pub kind: OgImageMarketKind<'a>,
     /// Graph data containing outcome-specific order history
     pub graph: &'a [OgImageGraphData<'a>],
+    /// Unix timestamp at which the market closes, shown as "Ends in 3d" or
+    /// "Closed Apr 21"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closes_at: Option<u64>,
+    /// Unix timestamp at which the market was resolved, shown as
+    /// "Resolved Apr 21"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<u64>,
+}
+
+/// Kind of prediction market, determining the range and formatting of the
+/// [`value`](OgImageDataPoint::value) of the graph data points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OgImageMarketKind<'a> {
+    /// Outcomes with a probability between 0 and 100 percent
+    #[default]
+    Probability,
+    /// A numeric value within a range, like "What will BTC close at?"
+    Scalar(OgImageScalarData<'a>),
+}
+
+impl OgImageMarketKind<'_> {
+    /// Returns the lowest and the highest value of the chart.
+    pub fn range(&self) -> (f64, f64) {
+        match self {
+            Self::Probability => (0., 100.),
+            Self::Scalar(scalar) => (scalar.min, scalar.max),
+        }
+    }
+}
+
+/// Range and formatting of a scalar market.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OgImageScalarData<'a> {
+    /// Lowest value of the range
+    pub min: f64,
+    /// Highest value of the range
+    pub max: f64,
+    /// Unit shown after the values, e.g. "USD" or "°C", may be empty
+    pub unit: &'a str,
+    /// Format of the values
+    pub format: NumberFormat,
+    /// Number of decimal places of the values
+    pub decimals: u8,
+}
+
+impl<'a> OgImageScalarData<'a> {
+    /// Creates a new `OgImageScalarData` with the specified range and unit,
+    /// and [plain](NumberFormat::Plain) number formatting without decimal
+    /// places.
+    pub const fn new(min: f64, max: f64, unit: &'a str) -> Self {
+        Self {
+            min,
+            max,
+            unit,
+            format: NumberFormat::Plain,
+            decimals: 0,
+        }
+    }
+
+    /// Sets the format of the values.
+    pub const fn with_format(mut self, format: NumberFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the number of decimal places of the values, e.g. 1 for "21.5 °C".
+    ///
+    /// # Panics
+    ///
+    /// Panics if `decimals` is greater than 4.
+    pub const fn with_decimals(mut self, decimals: u8) -> Self {
+        assert!(
+            decimals <= formatting::MAX_DECIMALS,
+            "decimals must be at most 4"
+        );
+        self.decimals = decimals;
+        self
+    }
+
+    /// Formats a value with the format, decimal places and unit of the
+    /// market.
+    pub fn format_value(&self, value: f64) -> String {
+        let number = self.format.format(value, self.decimals);
+        match self.unit {
+            "" => number,
+            unit => format!("{number} {unit}"),
+        }
+    }
+}
+
+/// Format of the values of scalar markets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberFormat {
+    /// All digits with thousands separators, like "64,250"
+    #[default]
+    Plain,
+    /// Abbreviated with "K" and "M" suffixes, like "64K", from 1,500 on
+    Compact,
+}
+
+impl NumberFormat {
+    /// Formats a number with the given number of decimal places.
+    ///
+    /// Compact numbers with a "K" or "M" suffix are shown without the decimal
+    /// places, like "64K".
+    pub fn format(&self, number: f64, decimals: u8) -> String {
+        let abs = number.abs();
+        match self {
+            Self::Compact if abs.round() >= 1500. => {
+                let sign = if number < 0. { "-" } else { "" };
+                let whole = abs.round().min(f64::from(u32::MAX)) as u32;
+                format!("{sign}{}", formatting::format_number(whole))
+            }
+            _ => formatting::format_decimal(number, decimals),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -37,12 +222,38 @@ pub struct OgImageCommunityData<'a> {
     pub handle: &'a str,
     /// Community avatar URL
     pub avatar: &'a str,
+    /// Community banner image URL, shown behind the header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner: Option<&'a str>,
+    /// Community brand color as hex code, used to tint the watermark
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<&'a str>,
 }
 
 impl<'a> OgImageCommunityData<'a> {
     /// Creates a new `OgImageCommunityData` with the specified handle and avatar URL.
     pub const fn new(handle: &'a str, avatar: &'a str) -> Self {
-        Self { handle, avatar }
+        Self {
+            handle,
+            avatar,
+            banner: None,
+            color: None,
+        }
+    }
+
+    /// Sets the URL of the community banner image.
+    ///
+    /// The banner is downloaded like the avatars. If the download fails, the
+    /// image is rendered with the regular header.
+    pub const fn with_banner(mut self, banner: &'a str) -> Self {
+        self.banner = Some(banner);
+        self
+    }
+
+    /// Sets the brand color of the community, e.g. `"#ff4500"`.
+    pub const fn with_color(mut self, color: &'a str) -> Self {
+        self.color = Some(color);
+        self
     }
 }
 
@@ -56,12 +267,13 @@ pub struct OgImageGraphData<'a> {
     pub data: &'a [OgImageDataPoint],
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OgImageDataPoint {
     /// Unix timestamp
     pub time: u64,
-    /// Value at that time
-    pub value: u32,
+    /// Value at that time, the probability in percent, like `0.4`, or the
+    /// value of [scalar](OgImageMarketKind::Scalar) markets
+    pub value: f64,
 }
 
 /// Author information for OpenGraph image generation
@@ -80,6 +292,40 @@ impl<'a> OgImageAuthorData<'a> {
     }
 }
 
+/// Outcome of downloading a single avatar.
+enum AvatarDownload {
+    /// The avatar was written to the assets directory with this filename
+    Downloaded(String),
+    /// The avatar was skipped and will not be rendered
+    Skipped(AvatarSkipReason),
+}
+
+/// Records the outcome of an avatar download in the metrics and passes it through.
+fn record_avatar_download(
+    download: Result<AvatarDownload, OgImageError>,
+) -> Result<AvatarDownload, OgImageError> {
+    let outcome = match &download {
+        Ok(AvatarDownload::Downloaded(_)) => AvatarDownloadOutcome::Ok,
+        Ok(AvatarDownload::Skipped(AvatarSkipReason::NotFound)) => AvatarDownloadOutcome::NotFound,
+        Ok(AvatarDownload::Skipped(AvatarSkipReason::UnsupportedFormat)) => {
+            AvatarDownloadOutcome::Unsupported
+        }
+        Ok(AvatarDownload::Skipped(AvatarSkipReason::DownloadFailed)) | Err(_) => {
+            AvatarDownloadOutcome::Error
+        }
+    };
+
+    telemetry::record_avatar_download(outcome);
+    download
+}
+
+/// Resolution of PNG images at a scale of `1.0`, resulting in 1200×630 pixel
+/// images for the 600×315pt OpenGraph layout.
+const PIXELS_PER_INCH: f32 = 144.0;
+
+/// Maximum duration of the Typst compilation before it is aborted.
+const DEFAULT_RENDER_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Generator for creating OpenGraph images using the Typst typesetting system.
 ///
 /// This struct manages the path to the Typst binary and provides methods for
@@ -88,6 +334,18 @@ pub struct OgImageGenerator {
     typst_binary_path: PathBuf,
     typst_font_path: Option<PathBuf>,
     oxipng_binary_path: PathBuf,
+    optimization: OptimizationConfig,
+    fallback_image: Option<FallbackImage>,
+    render_timeout: Duration,
+    render_cache: Option<Arc<dyn RenderCache>>,
+    format: ImageFormat,
+    scale: f32,
+    layout: Layout,
+    avatar_cache: Option<AvatarCache>,
+    templates: BTreeMap<CardKind, Template>,
+    theme: Theme,
+    chart: ChartOptions,
+    now: Option<u64>,
 }
 
 impl OgImageGenerator {
@@ -133,6 +391,8 @@ impl OgImageGenerator {
     ///
     /// If the `TYPST_PATH` environment variable is set, uses that path.
     /// Otherwise, falls back to the default behavior (assumes "typst" is in PATH).
+    /// If `OG_IMAGE_TEMPLATE_DIR` is set, the template is loaded from that
+    /// directory, see [`Template::from_dir()`].
     ///
     /// # Examples
     ///
@@ -147,6 +407,7 @@ impl OgImageGenerator {
         let typst_path = var("TYPST_PATH").map_err(OgImageError::EnvVarError)?;
         let font_path = var("TYPST_FONT_PATH").map_err(OgImageError::EnvVarError)?;
         let oxipng_path = var("OXIPNG_PATH").map_err(OgImageError::EnvVarError)?;
+        let template_dir = var("OG_IMAGE_TEMPLATE_DIR").map_err(OgImageError::EnvVarError)?;
 
         let mut generator = OgImageGenerator::default();
 
@@ -171,6 +432,14 @@ impl OgImageGenerator {
             debug!("OXIPNG_PATH not set, defaulting to 'oxipng' in PATH");
         };
 
+        if let Some(ref template_dir) = template_dir {
+            debug!(template_dir = %template_dir, "Loading template from environment");
+            let template = Template::from_dir(template_dir)?;
+            generator.templates.insert(CardKind::Market, template);
+        } else {
+            debug!("OG_IMAGE_TEMPLATE_DIR not set, using the builtin market template");
+        }
+
         Ok(generator)
     }
 
@@ -218,6 +487,7 @@ impl OgImageGenerator {
     ///
     /// This allows specifying a custom path to the oxipng binary for PNG optimization.
     /// If not set, defaults to "oxipng" which assumes the binary is available in PATH.
+    /// The binary is not used if the `oxipng` cargo feature is enabled.
     ///
     /// # Examples
     ///
@@ -233,61 +503,392 @@ impl OgImageGenerator {
         self
     }
 
+    /// Sets the PNG optimization configuration.
+    ///
+    /// Defaults to [`OptimizationConfig::default()`]. Use
+    /// [`OptimizationConfig::off()`] to skip the optimization step entirely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::{OgImageGenerator, OptimizationConfig};
+    ///
+    /// let generator = OgImageGenerator::default()
+    ///     .with_optimization(OptimizationConfig::level(4));
+    /// ```
+    pub fn with_optimization(mut self, optimization: OptimizationConfig) -> Self {
+        self.optimization = optimization;
+        self
+    }
+
+    /// Sets the output format of generated images.
+    ///
+    /// Defaults to [`ImageFormat::Png`]. The PNG optimization step is skipped
+    /// for other formats. Fallback images are always PNG images.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::{ImageFormat, OgImageGenerator};
+    ///
+    /// let generator = OgImageGenerator::default().with_format(ImageFormat::Svg);
+    /// ```
+    pub fn with_format(mut self, format: ImageFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the scale factor of generated PNG images.
+    ///
+    /// At the default scale of `1.0`, images are rendered at 144 pixels per
+    /// inch, resulting in 1200×630 pixel images for the
+    /// [`Layout::OpenGraph`] layout. The scale has no effect on SVG and PDF
+    /// images.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scale` is not a positive, finite number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::OgImageGenerator;
+    ///
+    /// // Render 2400×1260 pixel images
+    /// let generator = OgImageGenerator::default().with_scale(2.0);
+    /// ```
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        assert!(
+            scale.is_finite() && scale > 0.0,
+            "scale must be a positive, finite number, got {scale}"
+        );
+        self.scale = scale;
+        self
+    }
+
+    /// Sets the page layout of generated images.
+    ///
+    /// Defaults to [`Layout::OpenGraph`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::{Layout, OgImageGenerator};
+    ///
+    /// let generator = OgImageGenerator::default().with_layout(Layout::Square);
+    /// ```
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Enables fallback mode with the given fallback image.
+    ///
+    /// If rendering fails (e.g. because Typst is not installed, or the
+    /// compilation fails or times out), [`generate()`](Self::generate)
+    /// returns the fallback image instead of an error. The returned
+    /// [`OgImageOutput`] is flagged as [`degraded`](OgImageOutput::degraded),
+    /// so callers can decide whether to cache it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::{FallbackImage, OgImageGenerator};
+    ///
+    /// let generator = OgImageGenerator::default()
+    ///     .with_fallback_image(FallbackImage::Bundled);
+    /// ```
+    pub fn with_fallback_image(mut self, fallback_image: FallbackImage) -> Self {
+        self.fallback_image = Some(fallback_image);
+        self
+    }
+
+    /// Sets the maximum duration of the Typst compilation.
+    ///
+    /// Compilations that take longer are aborted and the Typst process is
+    /// killed, failing with [`OgImageError::TypstTimeout`], or returning the
+    /// [fallback image](Self::with_fallback_image) if one was configured.
+    /// Defaults to 30 seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crates_io_og_image::OgImageGenerator;
+    ///
+    /// let generator = OgImageGenerator::default()
+    ///     .with_render_timeout(Duration::from_secs(10));
+    /// ```
+    pub fn with_render_timeout(mut self, render_timeout: Duration) -> Self {
+        self.render_timeout = render_timeout;
+        self
+    }
+
+    /// Sets the render cache consulted before compiling an image.
+    ///
+    /// Images are looked up by their [`cache_key()`](Self::cache_key), and
+    /// successfully rendered images are stored in the cache. Degraded
+    /// fallback images are never cached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::{MemoryRenderCache, OgImageGenerator};
+    ///
+    /// let generator = OgImageGenerator::default()
+    ///     .with_render_cache(MemoryRenderCache::new(100));
+    /// ```
+    pub fn with_render_cache(mut self, render_cache: impl RenderCache + 'static) -> Self {
+        self.render_cache = Some(Arc::new(render_cache));
+        self
+    }
+
+    /// Sets the template used to render market images.
+    ///
+    /// Defaults to the builtin [market template](Template::market). Use
+    /// [`with_card_template()`](Self::with_card_template) to replace the
+    /// templates of the other card kinds.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crates_io_og_image::{OgImageGenerator, Template};
+    ///
+    /// let template = Template::from_dir("templates/my-card")?;
+    /// let generator = OgImageGenerator::default().with_template(template);
+    /// # Ok::<(), crates_io_og_image::OgImageError>(())
+    /// ```
+    pub fn with_template(self, template: Template) -> Self {
+        self.with_card_template(CardKind::Market, template)
+    }
+
+    /// Sets the template used to render images of the given card kind.
+    ///
+    /// Defaults to the builtin template of the card kind, see
+    /// [`Template::builtin_for()`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crates_io_og_image::{CardKind, OgImageGenerator, Template};
+    ///
+    /// let template = Template::from_dir("templates/my-profile")?;
+    /// let generator = OgImageGenerator::default().with_card_template(CardKind::Profile, template);
+    /// # Ok::<(), crates_io_og_image::OgImageError>(())
+    /// ```
+    pub fn with_card_template(mut self, kind: CardKind, template: Template) -> Self {
+        self.templates.insert(kind, template);
+        self
+    }
+
+    /// Returns the template used to render images of the given card kind.
+    pub(crate) fn template(&self, kind: CardKind) -> &Template {
+        &self.templates[&kind]
+    }
+
+    /// Sets the color theme of the rendered images.
+    ///
+    /// Defaults to [`Theme::dark()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::{OgImageGenerator, Theme};
+    ///
+    /// let generator = OgImageGenerator::default().with_theme(Theme::light());
+    /// ```
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the options of the probability chart of market images.
+    ///
+    /// Defaults to a chart without date labels and gridlines, see
+    /// [`ChartOptions`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::{ChartOptions, OgImageGenerator, TimeLabels};
+    ///
+    /// let chart = ChartOptions::default().with_time_labels(TimeLabels::StartEnd);
+    /// let generator = OgImageGenerator::default().with_chart(chart);
+    /// ```
+    pub fn with_chart(mut self, chart: ChartOptions) -> Self {
+        self.chart = chart;
+        self
+    }
+
+    /// Sets the current time as a Unix timestamp.
+    ///
+    /// The closing and resolution times of markets are shown relative to the
+    /// current time, e.g. "Ends in 3d". Defaults to the system time; a fixed
+    /// time makes the rendered images reproducible, e.g. for snapshot tests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::OgImageGenerator;
+    ///
+    /// let generator = OgImageGenerator::default().with_now(1745229600);
+    /// ```
+    pub fn with_now(mut self, now: u64) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Returns the current time as a Unix timestamp.
+    fn now(&self) -> u64 {
+        self.now.unwrap_or_else(|| {
+            let elapsed = SystemTime::now().duration_since(UNIX_EPOCH);
+            elapsed.map_or(0, |elapsed| elapsed.as_secs())
+        })
+    }
+
+    /// Returns the closing or resolution time of a market, formatted
+    /// relative to the current time, like "Ends in 3d".
+    pub(crate) fn market_timing(&self, data: &OgImageData<'_>) -> Option<String> {
+        let (timezone, locale) = (self.chart.timezone, self.chart.locale);
+        let now = self.now();
+        formatting::format_market_timing(data.closes_at, data.resolved_at, now, timezone, locale)
+    }
+
+    /// Sets the avatar cache shared between generations.
+    ///
+    /// Avatars are usually downloaded for every generated image. With an
+    /// avatar cache, each avatar URL is only downloaded once, which speeds up
+    /// rendering many images with the same authors and communities. The
+    /// cache can be cloned to share it between multiple generators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crates_io_og_image::{AvatarCache, OgImageGenerator};
+    ///
+    /// let generator = OgImageGenerator::default()
+    ///     .with_avatar_cache(AvatarCache::new(1000));
+    /// ```
+    pub fn with_avatar_cache(mut self, avatar_cache: AvatarCache) -> Self {
+        self.avatar_cache = Some(avatar_cache);
+        self
+    }
+
     /// Processes avatars by downloading URLs and copying assets to the assets directory.
     ///
     /// This method handles URL-based avatars (which are downloaded from the internet).
-    /// Returns a mapping from avatar source to the local filename.
-    #[instrument(skip(self, data), fields(question = %data.question))]
+    /// Returns a mapping from avatar source to the local filename, and the list
+    /// of avatars that were skipped.
+    #[instrument(skip(self, card), fields(kind = %card.kind()))]
     async fn process_avatars<'a>(
         &self,
-        data: &'a OgImageData<'_>,
+        card: &OgImageCard<'a>,
         assets_dir: &Path,
-    ) -> Result<HashMap<&'a str, String>, OgImageError> {
+    ) -> Result<(HashMap<&'a str, String>, Vec<SkippedAvatar>), OgImageError> {
         let mut avatar_map = HashMap::new();
+        let mut skipped_avatars = Vec::new();
         let client = reqwest::Client::new();
 
-        // Process author avatar
-        let author_avatar = &data.author.avatar;
+        for image in card.images() {
+            let CardImage {
+                url,
+                file_stem,
+                optional,
+            } = image;
+
+            debug!(url = %url, file_stem, "Processing {file_stem}");
+
+            let download = self
+                .download_avatar(&client, url, file_stem, assets_dir)
+                .await;
+
+            // Optional images, like community banners, fall back to the
+            // regular layout instead of failing the generation
+            let download = match record_avatar_download(download) {
+                Err(err) if optional => {
+                    warn!(url = %url, error = %err, "Failed to download {file_stem}");
+                    AvatarDownload::Skipped(AvatarSkipReason::DownloadFailed)
+                }
+                download => download?,
+            };
+
+            match download {
+                AvatarDownload::Downloaded(filename) => {
+                    avatar_map.insert(url, filename);
+                }
+                AvatarDownload::Skipped(reason) => {
+                    let url = url.to_string();
+                    skipped_avatars.push(SkippedAvatar { url, reason });
+                }
+            }
+        }
+
+        Ok((avatar_map, skipped_avatars))
+    }
+
+    /// Downloads a single avatar and saves it to the assets directory.
+    /// Returns the filename if successful, or the reason why the avatar should be skipped.
+    ///
+    /// If an avatar cache is configured, previously downloaded avatars are
+    /// taken from the cache instead of being downloaded again.
+    async fn download_avatar(
+        &self,
+        client: &reqwest::Client,
+        avatar_url: &str,
+        file_stem: &str,
+        assets_dir: &Path,
+    ) -> Result<AvatarDownload, OgImageError> {
+        let cached = self.avatar_cache.as_ref();
+        let avatar = match cached.and_then(|cache| cache.get(avatar_url)) {
+            Some(avatar) => {
+                debug!(url = %avatar_url, "Using cached avatar");
+                avatar
+            }
+            None => {
+                let avatar = Self::fetch_avatar(client, avatar_url).await?;
+                if let Some(avatar_cache) = &self.avatar_cache {
+                    avatar_cache.insert(avatar_url, avatar.clone());
+                }
+                avatar
+            }
+        };
+
+        let (bytes, extension) = match avatar {
+            AvatarImage::Image { bytes, extension } => (bytes, extension),
+            AvatarImage::Skipped(reason) => return Ok(AvatarDownload::Skipped(reason)),
+        };
+
+        let filename = format!("{file_stem}.{extension}");
+        let avatar_path = assets_dir.join(&filename);
+
         debug!(
-            author_name = %data.author.name,
-            avatar_url = %author_avatar,
-            "Processing avatar for author {}", data.author.name
+            avatar_url = %avatar_url,
+            avatar_path = %avatar_path.display(),
+            "Writing avatar file with detected format"
         );
 
-        if let Some(filename) = self
-            .download_avatar(&client, author_avatar, "author", assets_dir)
-            .await?
-        {
-            avatar_map.insert(author_avatar.as_ref(), filename);
-        }
+        // Write the bytes to the avatar file
+        fs::write(&avatar_path, &bytes)
+            .await
+            .map_err(|err| OgImageError::AvatarWriteError {
+                path: avatar_path.clone(),
+                source: err,
+            })?;
 
-        // Process community avatar
-        let community_avatar = &data.community.avatar;
         debug!(
-            community_handle = %data.community.handle,
-            avatar_url = %community_avatar,
-            "Processing avatar for community {}", data.community.handle
+            path = %avatar_path.display(),
+            size_bytes = bytes.len(),
+            "Avatar processed and written successfully"
         );
 
-        if let Some(filename) = self
-            .download_avatar(&client, community_avatar, "community", assets_dir)
-            .await?
-        {
-            avatar_map.insert(community_avatar.as_ref(), filename);
-        }
-
-        Ok(avatar_map)
+        Ok(AvatarDownload::Downloaded(filename))
     }
 
-    /// Downloads a single avatar and saves it to the assets directory.
-    /// Returns the filename if successful, None if the avatar should be skipped.
-    async fn download_avatar(
-        &self,
+    /// Downloads a single avatar and detects its image format.
+    async fn fetch_avatar(
         client: &reqwest::Client,
         avatar_url: &str,
-        prefix: &str,
-        assets_dir: &Path,
-    ) -> Result<Option<String>, OgImageError> {
+    ) -> Result<AvatarImage, OgImageError> {
         debug!(url = %avatar_url, "Downloading avatar from URL: {avatar_url}");
         let response = client.get(avatar_url).send().await.map_err(|err| {
             OgImageError::AvatarDownloadError {
@@ -299,7 +900,7 @@ impl OgImageGenerator {
         let status = response.status();
         if status == StatusCode::NOT_FOUND {
             warn!(url = %avatar_url, "Avatar URL returned 404 Not Found");
-            return Ok(None); // Skip this avatar if not found
+            return Ok(AvatarImage::Skipped(AvatarSkipReason::NotFound));
         }
 
         if let Err(err) = response.error_for_status_ref() {
@@ -341,45 +942,37 @@ impl OgImageGenerator {
             warn!("Unsupported avatar format at {avatar_url}, first 20 bytes: {hex_bytes}");
 
             // Skip this avatar and continue with the next one
-            return Ok(None);
+            return Ok(AvatarImage::Skipped(AvatarSkipReason::UnsupportedFormat));
         };
 
-        let filename = format!("{prefix}_avatar.{extension}");
-        let avatar_path = assets_dir.join(&filename);
-
-        debug!(
-            avatar_url = %avatar_url,
-            avatar_path = %avatar_path.display(),
-            "Writing avatar file with detected format"
-        );
-
-        // Write the bytes to the avatar file
-        fs::write(&avatar_path, &bytes)
-            .await
-            .map_err(|err| OgImageError::AvatarWriteError {
-                path: avatar_path.clone(),
-                source: err,
-            })?;
-
-        debug!(
-            path = %avatar_path.display(),
-            size_bytes = bytes.len(),
-            "Avatar processed and written successfully"
-        );
-
-        Ok(Some(filename))
-    }
+        let bytes = bytes.as_ref().into();
+        Ok(AvatarImage::Image { bytes, extension })
+    }
 
     /// Generates an OpenGraph image using the provided data.
     ///
+    /// The data can be an [`OgImageData`] for prediction markets, or any other
+    /// card data convertible into an [`OgImageCard`]. The image is rendered
+    /// with the template of the corresponding [`CardKind`].
+    ///
     /// This method creates a temporary directory with all the necessary files
     /// to create the OpenGraph image, compiles it to PNG using the Typst
-    /// binary, and returns the resulting image as a `NamedTempFile`.
+    /// binary, and returns the resulting image as an [`OgImageOutput`],
+    /// together with metadata such as its dimensions, content hash, per-stage
+    /// timings and any warnings emitted by the Typst compiler.
+    ///
+    /// If a render cache was configured with
+    /// [`with_render_cache()`](Self::with_render_cache), a cached image for the
+    /// same [`cache_key()`](Self::cache_key) is returned without rendering.
+    ///
+    /// If a fallback image was configured with
+    /// [`with_fallback_image()`](Self::with_fallback_image), rendering errors
+    /// are logged and the fallback image is returned instead.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use crates_io_og_image::{OgImageGenerator, OgImageData, OgImageAuthorData, OgImageCommunityData, OgImageError};
+    /// use crates_io_og_image::{OgImageGenerator, OgImageData, OgImageAuthorData, OgImageCommunityData, OgImageError, OgImageMarketKind};
     ///
     /// # #[tokio::main]
     /// # async fn main() -> Result<(), OgImageError> {
@@ -389,46 +982,148 @@ impl OgImageGenerator {
     ///     author: OgImageAuthorData::new("user", "https://example.com/avatar.png"),
     ///     community: OgImageCommunityData::new("climate", "https://example.com/community.png"),
     ///     outcome: "NONE",
+    ///     kind: OgImageMarketKind::Probability,
     ///     graph: &[],
+    ///     closes_at: None,
+    ///     resolved_at: None,
     /// };
-    /// let image_file = generator.generate(data).await?;
-    /// println!("Generated image at: {:?}", image_file.path());
+    /// let output = generator.generate(data).await?;
+    /// println!("Generated image at: {:?}", output.image.path());
     /// # Ok(())
     /// # }
     /// ```
-    #[instrument(skip(self, data), fields(
-        question = %data.question,
-        author = %data.author.name,
-        community = %data.community.handle,
-    ))]
-    pub async fn generate(&self, data: OgImageData<'_>) -> Result<NamedTempFile, OgImageError> {
-        let start_time = std::time::Instant::now();
+    pub async fn generate<'a>(
+        &self,
+        card: impl Into<OgImageCard<'a>>,
+    ) -> Result<OgImageOutput, OgImageError> {
+        self.generate_card(card.into()).await
+    }
+
+    #[instrument(skip(self, card), fields(kind = %card.kind()))]
+    async fn generate_card(&self, card: OgImageCard<'_>) -> Result<OgImageOutput, OgImageError> {
+        let start_time = Instant::now();
+
+        // Reject invalid data before it fails inside the template. This is
+        // done before rendering, so the error is not masked by the fallback
+        // image.
+        let result = match card.validate() {
+            Err(err) => Err(err),
+            Ok(()) => {
+                // Prepare the series of the graph once, for both the cache
+                // key and the template
+                let series = self.card_series(&card);
+                let graph = series.iter().map(ChartSeries::graph).collect::<Vec<_>>();
+                let card = with_series(card, &graph);
+
+                match (self.render(card, start_time).await, &self.fallback_image) {
+                    (Ok(output), _) => Ok(output),
+                    (Err(err), None) => Err(err),
+                    (Err(err), Some(fallback_image)) => {
+                        warn!(error = %err, "OpenGraph image generation failed, using fallback image");
+                        self.write_fallback_image(fallback_image, start_time).await
+                    }
+                }
+            }
+        };
+
+        match &result {
+            Ok(output) if output.degraded => {
+                telemetry::record_generation(GenerationResult::Fallback);
+                telemetry::record_output_size(output.size);
+            }
+            Ok(output) => {
+                telemetry::record_generation(GenerationResult::Success);
+                telemetry::record_output_size(output.size);
+            }
+            Err(_) => telemetry::record_generation(GenerationResult::Error),
+        }
+
+        result
+    }
+
+    /// Writes the fallback image to a temporary file and returns it as a
+    /// degraded [`OgImageOutput`].
+    async fn write_fallback_image(
+        &self,
+        fallback_image: &FallbackImage,
+        start_time: Instant,
+    ) -> Result<OgImageOutput, OgImageError> {
+        let output_file = NamedTempFile::new().map_err(OgImageError::TempFileError)?;
+        let bytes = fallback_image.bytes();
+        fs::write(output_file.path(), bytes).await?;
+
+        let info = ImageInfo::new(ImageFormat::Png, bytes);
+        let timings = OgImageTimings {
+            total: start_time.elapsed(),
+            ..Default::default()
+        };
+
+        Ok(OgImageOutput {
+            image: output_file,
+            format: info.format,
+            dimensions: info.dimensions,
+            size: info.size,
+            content_hash: info.content_hash,
+            timings,
+            avatar_count: 0,
+            skipped_avatars: Vec::new(),
+            warnings: Vec::new(),
+            degraded: true,
+            optimization: None,
+            cache_key: None,
+            cache_hit: false,
+        })
+    }
+
+    /// Renders the OpenGraph image without applying the fallback mode.
+    ///
+    /// The series of the card must already be prepared with
+    /// [`card_series()`](Self::card_series).
+    async fn render(
+        &self,
+        card: OgImageCard<'_>,
+        start_time: Instant,
+    ) -> Result<OgImageOutput, OgImageError> {
         info!("Starting OpenGraph image generation");
 
+        let json_chart = self.chart_json(&card)?;
+        let cache_key = self.prepared_cache_key(&card, json_chart.as_deref())?;
+        debug!(cache_key = %cache_key, "Computed render cache key");
+
+        if let Some(output) = self.read_cached_image(&cache_key, start_time).await? {
+            return Ok(output);
+        }
+
+        // Check the data against the input schema of the template
+        let template = self.template(card.kind());
+        let data_value =
+            serde_json::to_value(&card).map_err(OgImageError::JsonSerializationError)?;
+        template.schema().validate(template.name(), &data_value)?;
+
         // Create a temporary folder
         let temp_dir = tempfile::tempdir().map_err(OgImageError::TempDirError)?;
         debug!(temp_dir = %temp_dir.path().display(), "Created temporary directory");
 
-        // Create assets directory and copy logo and icons
+        // Create assets directory for the avatars
         let assets_dir = temp_dir.path().join("assets");
         debug!(assets_dir = %assets_dir.display(), "Creating assets directory");
         fs::create_dir(&assets_dir).await?;
 
-        debug!("Copying bundled assets to temporary directory");
-        // Copy prediction market SVG icons
-        let inertia_svg = include_bytes!("../template/assets/inertia.svg");
-        fs::write(assets_dir.join("inertia.svg"), inertia_svg).await?;
-        let likes_svg = include_bytes!("../template/assets/likes.svg");
-        fs::write(assets_dir.join("likes.svg"), likes_svg).await?;
-        let og_template_svg = include_bytes!("../template/assets/og-template.svg");
-        fs::write(assets_dir.join("og-template.svg"), og_template_svg).await?;
-        let volume_svg = include_bytes!("../template/assets/volume.svg");
-        fs::write(assets_dir.join("volume.svg"), volume_svg).await?;
+        // Copy the Typst template and its assets
+        debug!(template = %template.name(), "Copying template files to temporary directory");
+        for (path, contents) in template.files() {
+            let path = temp_dir.path().join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::write(&path, contents).await?;
+        }
+        let typ_file_path = temp_dir.path().join(template.main_path());
 
         // Process avatars - download URLs and copy assets
-        let avatar_start_time = std::time::Instant::now();
+        let avatar_start_time = Instant::now();
         info!("Processing avatars");
-        let avatar_map = self.process_avatars(&data, &assets_dir).await?;
+        let (avatar_map, skipped_avatars) = self.process_avatars(&card, &assets_dir).await?;
         let avatar_duration = avatar_start_time.elapsed();
         info!(
             avatar_count = avatar_map.len(),
@@ -436,35 +1131,57 @@ impl OgImageGenerator {
             "Avatar processing completed"
         );
 
-        // Copy the static Typst template file
-        let template_content = include_str!("../template/og-image.typ");
-        let typ_file_path = temp_dir.path().join("og-image.typ");
-        debug!(template_path = %typ_file_path.display(), "Copying Typst template");
-        fs::write(&typ_file_path, template_content).await?;
-
         // Create a named temp file for the output PNG
         let output_file = NamedTempFile::new().map_err(OgImageError::TempFileError)?;
         debug!(output_path = %output_file.path().display(), "Created output file");
 
-        // Serialize data and avatar_map to JSON
-        debug!("Serializing data and avatar map to JSON");
-        let json_data =
-            serde_json::to_string(&data).map_err(OgImageError::JsonSerializationError)?;
+        // Write the data, avatar map and chart options as JSON files into the
+        // template root. Passing them as command-line arguments would expose
+        // them in the process list and fail for large graphs.
+        debug!("Writing data, avatar map and chart options as JSON files");
+        let json_data = serde_json::to_vec(&card).map_err(OgImageError::JsonSerializationError)?;
+        fs::write(temp_dir.path().join(DATA_FILE), json_data).await?;
 
         let json_avatar_map =
-            serde_json::to_string(&avatar_map).map_err(OgImageError::JsonSerializationError)?;
+            serde_json::to_vec(&avatar_map).map_err(OgImageError::JsonSerializationError)?;
+        fs::write(temp_dir.path().join(AVATAR_MAP_FILE), json_avatar_map).await?;
+
+        if let Some(json_chart) = json_chart {
+            fs::write(temp_dir.path().join(CHART_FILE), json_chart).await?;
+        }
+
+        let json_theme =
+            serde_json::to_string(&self.theme).map_err(OgImageError::JsonSerializationError)?;
 
         // Run typst compile command with input data
         info!("Running Typst compilation command");
         let mut command = Command::new(&self.typst_binary_path);
-        command.arg("compile").arg("--format").arg("png");
+        command
+            .arg("compile")
+            .arg("--format")
+            .arg(self.format.extension());
+
+        if self.format == ImageFormat::Png {
+            let ppi = PIXELS_PER_INCH * self.scale;
+            command.arg("--ppi").arg(ppi.to_string());
+        }
+
+        // Use the machine-readable diagnostic format so we can parse errors and warnings
+        command.arg("--diagnostic-format").arg("short");
 
-        // Pass in the data and avatar map as JSON inputs
-        let input = format!("data={json_data}");
+        // Pass in the layout and theme as inputs
+        let input = format!("layout={}", self.layout);
         command.arg("--input").arg(input);
-        let input = format!("avatar_map={json_avatar_map}");
+        let input = format!("theme={json_theme}");
         command.arg("--input").arg(input);
 
+        // Pass in the closing or resolution time of markets, like "Ends in 3d"
+        if let OgImageCard::Market(data) = &card
+            && let Some(timing) = self.market_timing(data)
+        {
+            command.arg("--input").arg(format!("timing={timing}"));
+        }
+
         // Pass in the font path if specified
         if let Some(font_path) = &self.typst_font_path {
             debug!(font_path = %font_path.display(), "Using custom font path");
@@ -488,13 +1205,26 @@ impl OgImageGenerator {
             command.env("HOME", home);
         }
 
-        let compilation_start_time = std::time::Instant::now();
-        let output = command.output().await;
+        // Kill the Typst process if the compilation is aborted by the timeout
+        command.kill_on_drop(true);
+
+        let compilation_start_time = Instant::now();
+        let output = tokio::time::timeout(self.render_timeout, command.output()).await;
+        let Ok(output) = output else {
+            error!(
+                timeout_ms = self.render_timeout.as_millis(),
+                "Typst compilation timed out"
+            );
+            return Err(OgImageError::TypstTimeout(self.render_timeout));
+        };
         let output = output.map_err(OgImageError::TypstNotFound)?;
         let compilation_duration = compilation_start_time.elapsed();
+        telemetry::record_compile_duration(compilation_duration);
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let diagnostics = parse_diagnostics(&stderr);
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
             let stdout = String::from_utf8_lossy(&output.stdout).to_string();
             error!(
                 exit_code = ?output.status.code(),
@@ -507,92 +1237,126 @@ impl OgImageGenerator {
                 stderr,
                 stdout,
                 exit_code: output.status.code(),
+                diagnostics,
             });
         }
 
-        let output_size_bytes = fs::metadata(output_file.path()).await;
-        let output_size_bytes = output_size_bytes.map(|m| m.len()).unwrap_or(0);
+        let warnings = diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.severity == DiagnosticSeverity::Warning)
+            .collect::<Vec<_>>();
+
+        for warning in &warnings {
+            warn!(diagnostic = %warning, "Typst compilation emitted a warning");
+        }
 
         debug!(
             duration_ms = compilation_duration.as_millis(),
-            output_size_bytes, "Typst compilation completed successfully"
+            "Typst compilation completed successfully"
         );
 
         // After successful Typst compilation, optimize the PNG
-        self.optimize_png(output_file.path()).await;
+        let optimization_start_time = Instant::now();
+        let optimization = match self.format {
+            ImageFormat::Png => self.optimize_png(output_file.path()).await,
+            ImageFormat::Svg | ImageFormat::Pdf => None,
+        };
+        let optimization_duration = optimization_start_time.elapsed();
 
-        let duration = start_time.elapsed();
-        info!(
-            duration_ms = duration.as_millis(),
-            output_size_bytes, "OpenGraph image generation completed successfully"
-        );
-        Ok(output_file)
-    }
+        let bytes = fs::read(output_file.path()).await?;
+        let info = ImageInfo::new(self.format, &bytes);
 
-    /// Optimizes a PNG file using oxipng.
-    ///
-    /// This method attempts to reduce the file size of a PNG using lossless compression.
-    /// All errors are handled internally and logged as warnings. The method never fails
-    /// to ensure PNG optimization is truly optional.
-    async fn optimize_png(&self, png_file: &Path) {
-        debug!(
-            input_file = %png_file.display(),
-            oxipng_path = %self.oxipng_binary_path.display(),
-            "Starting PNG optimization"
-        );
+        if let Some(render_cache) = &self.render_cache
+            && let Err(err) = render_cache.put(&cache_key, &bytes, self.format).await
+        {
+            warn!(error = %err, cache_key = %cache_key, "Failed to store image in render cache");
+        }
 
-        let start_time = std::time::Instant::now();
+        let timings = OgImageTimings {
+            avatars: avatar_duration,
+            compilation: compilation_duration,
+            optimization: optimization_duration,
+            total: start_time.elapsed(),
+        };
 
-        let mut command = Command::new(&self.oxipng_binary_path);
+        info!(
+            duration_ms = timings.total.as_millis(),
+            output_size_bytes = info.size,
+            content_hash = %info.content_hash,
+            "OpenGraph image generation completed successfully"
+        );
 
-        // Default optimization level for speed/compression balance
-        command.arg("--opt").arg("2");
+        Ok(OgImageOutput {
+            image: output_file,
+            format: info.format,
+            dimensions: info.dimensions,
+            size: info.size,
+            content_hash: info.content_hash,
+            timings,
+            avatar_count: avatar_map.len(),
+            skipped_avatars,
+            warnings,
+            degraded: false,
+            optimization,
+            cache_key: Some(cache_key),
+            cache_hit: false,
+        })
+    }
 
-        // Remove safe-to-remove metadata
-        command.arg("--strip").arg("safe");
+    /// Looks up the image for the given cache key in the render cache.
+    ///
+    /// Returns `None` if no render cache is configured, the image is not
+    /// cached, or the lookup failed.
+    async fn read_cached_image(
+        &self,
+        cache_key: &str,
+        start_time: Instant,
+    ) -> Result<Option<OgImageOutput>, OgImageError> {
+        let Some(render_cache) = &self.render_cache else {
+            return Ok(None);
+        };
 
-        // Overwrite the input PNG file
-        command.arg(png_file);
+        let bytes = match render_cache.get(cache_key, self.format).await {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => {
+                debug!(cache_key = %cache_key, "Render cache miss");
+                telemetry::record_cache_lookup(CacheLookupResult::Miss);
+                return Ok(None);
+            }
+            Err(err) => {
+                warn!(error = %err, cache_key = %cache_key, "Failed to read from render cache");
+                telemetry::record_cache_lookup(CacheLookupResult::Miss);
+                return Ok(None);
+            }
+        };
 
-        // Clear environment variables to avoid leaking sensitive data
-        command.env_clear();
+        info!(cache_key = %cache_key, "Render cache hit, skipping compilation");
+        telemetry::record_cache_lookup(CacheLookupResult::Hit);
 
-        // Preserve environment variables needed for running oxipng
-        if let Ok(path) = std::env::var("PATH") {
-            command.env("PATH", path);
-        }
+        let output_file = NamedTempFile::new().map_err(OgImageError::TempFileError)?;
+        fs::write(output_file.path(), &bytes).await?;
 
-        let output = command.output().await;
-        let duration = start_time.elapsed();
+        let info = ImageInfo::new(self.format, &bytes);
+        let timings = OgImageTimings {
+            total: start_time.elapsed(),
+            ..Default::default()
+        };
 
-        match output {
-            Ok(output) if output.status.success() => {
-                debug!(
-                    duration_ms = duration.as_millis(),
-                    "PNG optimization completed successfully"
-                );
-            }
-            Ok(output) => {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                warn!(
-                    exit_code = ?output.status.code(),
-                    stderr = %stderr,
-                    stdout = %stdout,
-                    duration_ms = duration.as_millis(),
-                    input_file = %png_file.display(),
-                    "PNG optimization failed, continuing with unoptimized image"
-                );
-            }
-            Err(err) => {
-                warn!(
-                    error = %err,
-                    input_file = %png_file.display(),
-                    oxipng_path = %self.oxipng_binary_path.display(),
-                    "Failed to execute oxipng, continuing with unoptimized image"
-                );
-            }
-        }
+        Ok(Some(OgImageOutput {
+            image: output_file,
+            format: info.format,
+            dimensions: info.dimensions,
+            size: info.size,
+            content_hash: info.content_hash,
+            timings,
+            avatar_count: 0,
+            skipped_avatars: Vec::new(),
+            warnings: Vec::new(),
+            degraded: false,
+            optimization: None,
+            cache_key: Some(cache_key.to_string()),
+            cache_hit: true,
+        }))
     }
 }
 
@@ -605,6 +1369,21 @@ impl Default for OgImageGenerator {
             typst_binary_path: PathBuf::from("typst"),
             typst_font_path: None,
             oxipng_binary_path: PathBuf::from("oxipng"),
+            optimization: OptimizationConfig::default(),
+            fallback_image: None,
+            render_timeout: DEFAULT_RENDER_TIMEOUT,
+            render_cache: None,
+            format: ImageFormat::default(),
+            scale: 1.0,
+            layout: Layout::default(),
+            avatar_cache: None,
+            templates: CardKind::ALL
+                .into_iter()
+                .map(|kind| (kind, Template::builtin_for(kind)))
+                .collect(),
+            theme: Theme::default(),
+            chart: ChartOptions::default(),
+            now: None,
         }
     }
 }
@@ -613,6 +1392,7 @@ impl Default for OgImageGenerator {
 mod tests {
     use super::*;
     use mockito::{Server, ServerGuard};
+    use std::borrow::Cow;
     use tracing::dispatcher::DefaultGuard;
     use tracing::{Level, subscriber};
     use tracing_subscriber::fmt;
@@ -654,6 +1434,14 @@ mod tests {
             .with_body("Not Found")
             .create();
 
+        // Mock for avatar download in an unsupported format
+        server
+            .mock("GET", "/unsupported-avatar.gif")
+            .with_status(200)
+            .with_header("content-type", "image/gif")
+            .with_body("GIF89a")
+            .create();
+
         server
     }
 
@@ -666,7 +1454,10 @@ mod tests {
             author: OgImageAuthorData::new("test-user", author_avatar),
             community: OgImageCommunityData::new("test-community", community_avatar),
             outcome: "NONE",
+            kind: OgImageMarketKind::Probability,
             graph: &[],
+            closes_at: None,
+            resolved_at: None,
         }
     }
 
@@ -674,38 +1465,38 @@ mod tests {
         static DATA_POINTS_YES: &[OgImageDataPoint] = &[
             OgImageDataPoint {
                 time: 1744249342,
-                value: 50,
+                value: 50.,
             },
             OgImageDataPoint {
                 time: 1744249423,
-                value: 60,
+                value: 60.,
             },
             OgImageDataPoint {
                 time: 1744924887,
-                value: 5,
+                value: 5.,
             },
             OgImageDataPoint {
                 time: 1745010399,
-                value: 40,
+                value: 40.,
             },
         ];
 
         static DATA_POINTS_NO: &[OgImageDataPoint] = &[
             OgImageDataPoint {
                 time: 1744249342,
-                value: 50,
+                value: 50.,
             },
             OgImageDataPoint {
                 time: 1744249396,
-                value: 40,
+                value: 40.,
             },
             OgImageDataPoint {
                 time: 1744352237,
-                value: 25,
+                value: 25.,
             },
             OgImageDataPoint {
                 time: 1744757651,
-                value: 99,
+                value: 99.,
             },
         ];
 
@@ -730,20 +1521,566 @@ mod tests {
             author: OgImageAuthorData::new("@ai_researcher", author_avatar),
             community: OgImageCommunityData::new("AI Predictions", community_avatar),
             outcome: "NONE",
+            kind: OgImageMarketKind::Probability,
             graph: GRAPH,
+            closes_at: None,
+            resolved_at: None,
         }
     }
 
-    async fn generate_image(data: OgImageData<'_>) -> Option<Vec<u8>> {
+    async fn generate_image<'a>(data: impl Into<OgImageCard<'a>>) -> Option<Vec<u8>> {
         let generator =
             OgImageGenerator::from_environment().expect("Failed to create OgImageGenerator");
 
-        let temp_file = generator
+        let output = generator
             .generate(data)
             .await
             .expect("Failed to generate image");
 
-        Some(std::fs::read(temp_file.path()).expect("Failed to read generated image"))
+        Some(std::fs::read(output.image.path()).expect("Failed to read generated image"))
+    }
+
+    #[tokio::test]
+    async fn test_generate_fallback_with_broken_typst_path() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        let generator = OgImageGenerator::default()
+            .with_typst_path(PathBuf::from("/nonexistent/typst"))
+            .with_fallback_image(FallbackImage::Bundled);
+
+        let output = generator
+            .generate(data)
+            .await
+            .expect("Failed to generate fallback image");
+
+        assert!(output.degraded);
+        assert!(output.warnings.is_empty());
+
+        let image_data = std::fs::read(output.image.path()).expect("Failed to read image");
+        assert_eq!(image_data, FallbackImage::Bundled.bytes());
+        assert_eq!(
+            OgImageGenerator::detect_image_format(&image_data),
+            Some("png")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_custom_fallback_with_broken_typst_path() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        let custom_image = include_bytes!("../template/assets/test-avatar.png");
+        let generator = OgImageGenerator::default()
+            .with_typst_path(PathBuf::from("/nonexistent/typst"))
+            .with_fallback_image(FallbackImage::Custom(Cow::Borrowed(custom_image)));
+
+        let output = generator
+            .generate(data)
+            .await
+            .expect("Failed to generate fallback image");
+
+        assert!(output.degraded);
+
+        let image_data = std::fs::read(output.image.path()).expect("Failed to read image");
+        assert_eq!(image_data, custom_image);
+    }
+
+    #[tokio::test]
+    async fn test_generate_without_fallback_with_broken_typst_path() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        let generator =
+            OgImageGenerator::default().with_typst_path(PathBuf::from("/nonexistent/typst"));
+
+        let result = generator.generate(data).await;
+        assert!(matches!(result, Err(OgImageError::TypstNotFound(_))));
+    }
+
+    /// Writes a fake Typst binary that never finishes the compilation.
+    #[cfg(unix)]
+    fn create_sleeping_typst(dir: &Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("typst");
+        std::fs::write(&path, "#!/bin/sh\nsleep 60\n").expect("Failed to write fake Typst");
+        let permissions = std::fs::Permissions::from_mode(0o755);
+        std::fs::set_permissions(&path, permissions).expect("Failed to make fake Typst executable");
+        path
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_generate_with_render_timeout() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        let timeout = Duration::from_millis(200);
+        let generator = OgImageGenerator::default()
+            .with_typst_path(create_sleeping_typst(temp_dir.path()))
+            .with_render_timeout(timeout);
+
+        let start_time = Instant::now();
+        let result = generator.generate(data.clone()).await;
+        assert!(matches!(result, Err(OgImageError::TypstTimeout(t)) if t == timeout));
+        assert!(start_time.elapsed() < Duration::from_secs(10));
+
+        // The fallback image is returned for timed out compilations
+        let generator = generator.with_fallback_image(FallbackImage::Bundled);
+        let output = generator
+            .generate(data)
+            .await
+            .expect("Failed to generate fallback image");
+        assert!(output.degraded);
+    }
+
+    #[tokio::test]
+    async fn test_generate_returns_cached_image() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        let render_cache = Arc::new(MemoryRenderCache::new(10));
+        let generator = OgImageGenerator::default()
+            .with_typst_path(PathBuf::from("/nonexistent/typst"))
+            .with_render_cache(render_cache.clone());
+
+        let cache_key = generator.cache_key(&data).unwrap();
+        let fallback = FallbackImage::Bundled;
+        render_cache
+            .put(&cache_key, fallback.bytes(), ImageFormat::Png)
+            .await
+            .unwrap();
+
+        // Typst is not available, so the image can only come from the cache
+        let output = generator.generate(data).await.unwrap();
+        assert!(output.cache_hit);
+        assert!(!output.degraded);
+        assert_eq!(output.etag(), Some(format!("\"{cache_key}\"")));
+        assert_eq!(output.cache_key, Some(cache_key));
+        assert_eq!(output.content_hash, output::content_hash(fallback.bytes()));
+        assert_eq!(
+            std::fs::read(output.image.path()).unwrap(),
+            fallback.bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_does_not_cache_fallback_image() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        let render_cache = Arc::new(MemoryRenderCache::new(10));
+        let generator = OgImageGenerator::default()
+            .with_typst_path(PathBuf::from("/nonexistent/typst"))
+            .with_fallback_image(FallbackImage::Bundled)
+            .with_render_cache(render_cache.clone());
+
+        let output = generator.generate(data).await.unwrap();
+        assert!(output.degraded);
+        assert!(!output.cache_hit);
+        assert_eq!(output.etag(), None);
+        assert!(render_cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publish_uploads_image() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        // Typst is not available, so the image is served from the render cache
+        let render_cache = Arc::new(MemoryRenderCache::new(10));
+        let generator = OgImageGenerator::default()
+            .with_typst_path(PathBuf::from("/nonexistent/typst"))
+            .with_render_cache(render_cache.clone());
+
+        let png = FallbackImage::Bundled.bytes();
+        let cache_key = generator.cache_key(&data).unwrap();
+        render_cache
+            .put(&cache_key, png, ImageFormat::Png)
+            .await
+            .unwrap();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = FilesystemImageStore::new(temp_dir.path());
+        let options = PublishOptions::default().with_prefix("og/");
+
+        let published = generator.publish(data, &store, &options).await.unwrap();
+        assert_eq!(published.key, format!("og/{cache_key}.png"));
+        assert!(matches!(published.outcome, PublishOutcome::Uploaded(_)));
+
+        let path = temp_dir.path().join(&published.key);
+        assert_eq!(std::fs::read(path).unwrap(), png);
+    }
+
+    #[tokio::test]
+    async fn test_avatar_cache() {
+        let _guard = init_tracing();
+        let mut server = Server::new_async().await;
+        let avatar_mock = server
+            .mock("GET", "/avatar.png")
+            .with_body(include_bytes!("../template/assets/test-avatar.png"))
+            .expect(1)
+            .create_async()
+            .await;
+        let missing_mock = server
+            .mock("GET", "/missing.png")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let avatar_url = format!("{}/avatar.png", server.url());
+        let missing_url = format!("{}/missing.png", server.url());
+        let data = OgImageData {
+            author: OgImageAuthorData::new("test-user", &avatar_url),
+            community: OgImageCommunityData::new("test-community", &missing_url),
+            ..create_minimal_test_data(&server.url())
+        };
+
+        let avatar_cache = AvatarCache::new(10);
+        let generator = OgImageGenerator::default().with_avatar_cache(avatar_cache.clone());
+
+        for _ in 0..2 {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let (avatar_map, skipped) = generator
+                .process_avatars(&OgImageCard::from(&data), temp_dir.path())
+                .await
+                .unwrap();
+            assert_eq!(avatar_map.len(), 1);
+            assert_eq!(skipped.len(), 1);
+            assert!(temp_dir.path().join("author_avatar.png").exists());
+        }
+
+        assert_eq!(avatar_cache.len(), 2);
+        avatar_mock.assert_async().await;
+        missing_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_community_banner_fallback() {
+        let _guard = init_tracing();
+        let mut server = create_mock_avatar_server().await;
+        server
+            .mock("GET", "/broken-banner.png")
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let generator = OgImageGenerator::default();
+        let data = create_minimal_test_data(&server.url());
+
+        // A downloaded banner is added to the avatar map
+        let banner_url = format!("{}/test-avatar.jpg", server.url());
+        let data = OgImageData {
+            community: data.community.with_banner(&banner_url),
+            ..data
+        };
+        let temp_dir = tempfile::tempdir().unwrap();
+        let (avatar_map, skipped) = generator
+            .process_avatars(&OgImageCard::from(&data), temp_dir.path())
+            .await
+            .unwrap();
+        assert_eq!(avatar_map[banner_url.as_str()], "community_banner.jpg");
+        assert!(skipped.is_empty());
+
+        // A failed banner download is skipped instead of failing the generation
+        let banner_url = format!("{}/broken-banner.png", server.url());
+        let data = OgImageData {
+            community: data.community.with_banner(&banner_url),
+            ..data
+        };
+        let temp_dir = tempfile::tempdir().unwrap();
+        let (avatar_map, skipped) = generator
+            .process_avatars(&OgImageCard::from(&data), temp_dir.path())
+            .await
+            .unwrap();
+        assert!(!avatar_map.contains_key(banner_url.as_str()));
+        let reason = AvatarSkipReason::DownloadFailed;
+        assert_eq!(
+            skipped,
+            [SkippedAvatar {
+                url: banner_url,
+                reason
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_render_batch() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        // Typst is not available, so only the cached record can be rendered
+        let render_cache = Arc::new(MemoryRenderCache::new(10));
+        let generator = OgImageGenerator::default()
+            .with_typst_path(PathBuf::from("/nonexistent/typst"))
+            .with_render_cache(render_cache.clone());
+
+        let png = FallbackImage::Bundled.bytes();
+        let cache_key = generator.cache_key(&data).unwrap();
+        render_cache
+            .put(&cache_key, png, ImageFormat::Png)
+            .await
+            .unwrap();
+
+        let mut cached = serde_json::to_value(&data).unwrap();
+        cached["id"] = "market-1".into();
+        let mut uncached = serde_json::to_value(&data).unwrap();
+        uncached["question"] = "Will this be rendered?".into();
+        let input = format!("{cached}\n\nnot json\n{uncached}\n");
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let options = BatchOptions::new(temp_dir.path()).with_concurrency(2);
+
+        let mut report = Vec::new();
+        let summary = generator
+            .render_batch(input.as_bytes(), &mut report, &options)
+            .await
+            .unwrap();
+        assert_eq!(summary.succeeded, 1);
+        assert_eq!(summary.failed, 2);
+
+        let mut entries = report
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice::<BatchReportEntry>(line).unwrap())
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.line);
+
+        let lines = entries.iter().map(|entry| entry.line).collect::<Vec<_>>();
+        assert_eq!(lines, [1, 3, 4]);
+
+        let output = temp_dir.path().join("market-1.png");
+        let status = BatchStatus::Success {
+            output: output.clone(),
+            cache_hit: true,
+        };
+        assert_eq!(entries[0].id.as_deref(), Some("market-1"));
+        assert_eq!(entries[0].key.as_ref(), Some(&cache_key));
+        assert_eq!(entries[0].status, status);
+        assert_eq!(std::fs::read(output).unwrap(), png);
+
+        assert_eq!(entries[1].key, None);
+        assert!(matches!(entries[1].status, BatchStatus::Failed { .. }));
+        assert!(entries[2].key.is_some());
+        assert!(matches!(entries[2].status, BatchStatus::Failed { .. }));
+
+        // Resuming from the report skips the successfully rendered record
+        let completed = read_completed_records(report.as_slice()).await.unwrap();
+        let options = options.with_completed(completed);
+        let summary = generator
+            .render_batch(input.as_bytes(), tokio::io::sink(), &options)
+            .await
+            .unwrap();
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.succeeded, 0);
+        assert_eq!(summary.failed, 2);
+
+        // Completed records are skipped even if their cache key changed
+        let generator = generator.with_scale(2.0);
+        let summary = generator
+            .render_batch(input.as_bytes(), tokio::io::sink(), &options)
+            .await
+            .unwrap();
+        assert_eq!(summary.skipped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_render_batch_duplicate_ids() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        let render_cache = Arc::new(MemoryRenderCache::new(10));
+        let generator = OgImageGenerator::default()
+            .with_typst_path(PathBuf::from("/nonexistent/typst"))
+            .with_render_cache(render_cache.clone());
+
+        let png = FallbackImage::Bundled.bytes();
+        let cache_key = generator.cache_key(&data).unwrap();
+        render_cache
+            .put(&cache_key, png, ImageFormat::Png)
+            .await
+            .unwrap();
+
+        let mut record = serde_json::to_value(&data).unwrap();
+        record["id"] = "market-1".into();
+        let input = format!("{record}\n{record}\n");
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let options = BatchOptions::new(temp_dir.path()).with_concurrency(2);
+
+        let mut report = Vec::new();
+        let summary = generator
+            .render_batch(input.as_bytes(), &mut report, &options)
+            .await
+            .unwrap();
+        assert_eq!(summary.succeeded, 1);
+        assert_eq!(summary.failed, 1);
+
+        // The later record with the same id fails instead of overwriting the
+        // image of the first one
+        let entries = report
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice::<BatchReportEntry>(line).unwrap())
+            .collect::<Vec<_>>();
+        let failed = entries.iter().find(|entry| entry.line == 2).unwrap();
+        assert!(
+            matches!(&failed.status, BatchStatus::Failed { error } if error.contains("Duplicate id"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_publish_skips_existing_image() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        let generator =
+            OgImageGenerator::default().with_typst_path(PathBuf::from("/nonexistent/typst"));
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = FilesystemImageStore::new(temp_dir.path());
+        let options = PublishOptions::default().with_skip_existing(true);
+
+        let key = generator.object_key(&data, &options).unwrap();
+        let png = FallbackImage::Bundled.bytes();
+        store.put(&key, png, "image/png", "").await.unwrap();
+
+        // Typst is not available, so rendering would fail if it was attempted
+        let published = generator.publish(data, &store, &options).await.unwrap();
+        assert_eq!(published.key, key);
+        assert!(matches!(published.outcome, PublishOutcome::AlreadyExists));
+    }
+
+    #[tokio::test]
+    async fn test_publish_does_not_upload_degraded_image() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_minimal_test_data(&server_url);
+
+        let generator = OgImageGenerator::default()
+            .with_typst_path(PathBuf::from("/nonexistent/typst"))
+            .with_fallback_image(FallbackImage::Bundled);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = FilesystemImageStore::new(temp_dir.path());
+        let options = PublishOptions::default();
+
+        let published = generator.publish(data, &store, &options).await.unwrap();
+        assert!(matches!(published.outcome, PublishOutcome::Degraded(_)));
+        assert!(!store.exists(&published.key).await.unwrap());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_metrics_for_mock_server_generations() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let _guard = init_tracing();
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build runtime");
+
+            runtime.block_on(async {
+                let server = create_mock_avatar_server().await;
+                let server_url = server.url();
+
+                // Both avatars download successfully, rendering falls back
+                let generator = OgImageGenerator::default()
+                    .with_typst_path(PathBuf::from("/nonexistent/typst"))
+                    .with_fallback_image(FallbackImage::Bundled);
+
+                let data = create_minimal_test_data(&server_url);
+                let result = generator.generate(data).await;
+                assert!(result.expect("Failed to generate fallback image").degraded);
+
+                // Both avatars are skipped, rendering fails
+                let author_avatar = format!("{server_url}/missing-avatar.png");
+                let community_avatar = format!("{server_url}/unsupported-avatar.gif");
+                let data = OgImageData {
+                    question: "Will metrics be recorded?",
+                    author: OgImageAuthorData::new("test-user", &author_avatar),
+                    community: OgImageCommunityData::new("test-community", &community_avatar),
+                    outcome: "NONE",
+                    kind: OgImageMarketKind::Probability,
+                    graph: &[],
+                    closes_at: None,
+                    resolved_at: None,
+                };
+
+                let generator = OgImageGenerator::default()
+                    .with_typst_path(PathBuf::from("/nonexistent/typst"));
+                assert!(generator.generate(data).await.is_err());
+            });
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let value = |name: &str, label: Option<(&str, &str)>| {
+            snapshot
+                .iter()
+                .find(|(key, _, _, _)| {
+                    let key = key.key();
+                    key.name() == name
+                        && label.is_none_or(|(k, v)| {
+                            key.labels().any(|l| l.key() == k && l.value() == v)
+                        })
+                })
+                .map(|(_, _, _, value)| value)
+        };
+        let counter = |name: &str, label: (&str, &str)| match value(name, Some(label)) {
+            Some(DebugValue::Counter(count)) => *count,
+            other => panic!("Unexpected value for {name} {label:?}: {other:?}"),
+        };
+
+        assert_eq!(counter(GENERATIONS_TOTAL, ("result", "fallback")), 1);
+        assert_eq!(counter(GENERATIONS_TOTAL, ("result", "error")), 1);
+        assert_eq!(value(GENERATIONS_TOTAL, Some(("result", "success"))), None);
+
+        assert_eq!(counter(AVATAR_DOWNLOADS_TOTAL, ("outcome", "ok")), 2);
+        assert_eq!(counter(AVATAR_DOWNLOADS_TOTAL, ("outcome", "404")), 1);
+        assert_eq!(
+            counter(AVATAR_DOWNLOADS_TOTAL, ("outcome", "unsupported")),
+            1
+        );
+
+        let fallback_size = FallbackImage::Bundled.bytes().len() as f64;
+        match value(OUTPUT_SIZE_BYTES, None) {
+            Some(DebugValue::Histogram(values)) => {
+                assert_eq!(
+                    values.iter().map(|v| v.0).collect::<Vec<_>>(),
+                    [fallback_size]
+                );
+            }
+            other => panic!("Unexpected value for {OUTPUT_SIZE_BYTES}: {other:?}"),
+        }
     }
 
     #[tokio::test]
@@ -758,6 +2095,124 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_generate_og_image_time_scales() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_prediction_test_data(&server_url);
+
+        let time_scales = [
+            TimeScale::Real,
+            TimeScale::Compressed { max_gap: 3600 },
+            TimeScale::Ordinal,
+        ];
+        for time_scale in time_scales {
+            let chart = ChartOptions::default()
+                .with_time_scale(time_scale)
+                .with_time_labels(TimeLabels::StartMiddleEnd);
+            let generator = OgImageGenerator::from_environment()
+                .expect("Failed to create OgImageGenerator")
+                .with_chart(chart);
+
+            let output = generator.generate(&data).await;
+            let output = output.unwrap_or_else(|err| panic!("{time_scale}: {err}"));
+            assert!(!output.degraded);
+            assert!(output.size > 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_og_image_invalid_input() {
+        let _guard = init_tracing();
+        let graph = [OgImageGraphData {
+            outcome: "Yes",
+            color: "#00F29C",
+            data: &[],
+        }];
+        let data = OgImageData {
+            graph: &graph,
+            ..create_minimal_test_data("http://127.0.0.1:1")
+        };
+
+        // The data is rejected before any avatar is downloaded
+        let generator = OgImageGenerator::default();
+        let err = generator.generate(&data).await.unwrap_err();
+        let OgImageError::InvalidInput(errors) = &err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "graph[0].data");
+        assert_eq!(
+            err.to_string(),
+            "Invalid input data: graph[0].data: must contain at least one data point"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_og_image_invalid_input_with_fallback() {
+        let _guard = init_tracing();
+        let graph = [OgImageGraphData {
+            outcome: "Yes",
+            color: "#00F29C",
+            data: &[],
+        }];
+        let data = OgImageData {
+            graph: &graph,
+            ..create_minimal_test_data("http://127.0.0.1:1")
+        };
+
+        // Invalid input is reported instead of returning the fallback image
+        let generator = OgImageGenerator::default().with_fallback_image(FallbackImage::Bundled);
+        let result = generator.generate(&data).await;
+        assert!(matches!(result, Err(OgImageError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_generate_og_image_large_graph() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let server_url = server.url();
+        let data = create_prediction_test_data(&server_url);
+
+        // Several megabytes of JSON, far beyond the size of a single
+        // command-line argument
+        let points = (0..200_000)
+            .map(|index| OgImageDataPoint {
+                time: 1744249342 + index * 60,
+                value: (index % 100) as f64,
+            })
+            .collect::<Vec<_>>();
+        let graph = [OgImageGraphData {
+            outcome: "Yes",
+            color: "#00F29C",
+            data: &points,
+        }];
+        let data = OgImageData {
+            graph: &graph,
+            ..data
+        };
+        assert!(serde_json::to_vec(&data).unwrap().len() > 4 * 1024 * 1024);
+
+        // The template fails to compile unless it sees every data point
+        let source = b"#set page(width: 100pt, height: 100pt)\n\
+            #let data = json(\"data.json\")\n\
+            #let avatars = json(\"avatar_map.json\")\n\
+            #assert.eq(data.graph.at(0).data.len(), 200000)\n\
+            #assert.eq(avatars.len(), 2)\n\
+            #data.question";
+        let template = Template::new("graph", "main.typ", source.as_slice());
+        let chart = ChartOptions::default().with_max_points(usize::MAX);
+        let generator = OgImageGenerator::from_environment()
+            .expect("Failed to create OgImageGenerator")
+            .with_template(template)
+            .with_chart(chart);
+
+        let output = generator.generate(&data).await.unwrap();
+        assert!(!output.degraded);
+        assert!(output.size > 0);
+    }
+
     #[tokio::test]
     async fn test_generate_og_image_minimal_snapshot() {
         let _guard = init_tracing();
@@ -786,7 +2241,10 @@ mod tests {
             author: OgImageAuthorData::new("test-user", &author_avatar),
             community: OgImageCommunityData::new("test-community", &community_avatar),
             outcome: "NONE",
+            kind: OgImageMarketKind::Probability,
             graph: &[],
+            closes_at: None,
+            resolved_at: None,
         };
 
         if let Some(image_data) = generate_image(data).await {
@@ -811,11 +2269,116 @@ mod tests {
             author: OgImageAuthorData::new("@verbose_predictor", &author_avatar),
             community: OgImageCommunityData::new("Long Questions Community", &community_avatar),
             outcome: "NONE",
+            kind: OgImageMarketKind::Probability,
             graph: &[],
+            closes_at: None,
+            resolved_at: None,
         };
 
         if let Some(image_data) = generate_image(data).await {
             insta::assert_binary_snapshot!("long-question.png", image_data);
         }
     }
+
+    #[tokio::test]
+    async fn test_generate_og_image_market_timing() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+
+        // 2025-04-21 10:00:00 UTC
+        let now = 1745229600;
+        let generator = OgImageGenerator::from_environment()
+            .expect("Failed to create OgImageGenerator")
+            .with_now(now);
+
+        let data = OgImageData {
+            closes_at: Some(now + 3 * 24 * 60 * 60),
+            ..create_minimal_test_data(&server.url())
+        };
+        assert_eq!(
+            generator.market_timing(&data).as_deref(),
+            Some("Ends in 3d")
+        );
+
+        let output = generator
+            .generate(data)
+            .await
+            .expect("Failed to generate image");
+        let image_data = std::fs::read(output.image.path()).expect("Failed to read image");
+        insta::assert_binary_snapshot!("market-timing.png", image_data);
+    }
+
+    #[tokio::test]
+    async fn test_generate_og_image_profile_snapshot() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let avatar = format!("{}/test-avatar.png", server.url());
+
+        let data = OgImageProfileData {
+            name: "superforecaster",
+            avatar: &avatar,
+            predictions: 1523,
+            win_rate: Some(64),
+            profit_loss: 1_250_000,
+        };
+
+        if let Some(image_data) = generate_image(data).await {
+            insta::assert_binary_snapshot!("profile.png", image_data);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_og_image_community_snapshot() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let avatar = format!("{}/test-avatar.jpg", server.url());
+
+        let top_markets = [
+            OgImageMarketSummary {
+                question: "Will global temperatures rise by more than 1.5°C by 2030?",
+                volume: Some(125_000),
+            },
+            OgImageMarketSummary {
+                question: "Will the EU ban new combustion engine cars before 2035?",
+                volume: Some(8_400),
+            },
+            OgImageMarketSummary {
+                question: "Will carbon capture remove 1 Gt of CO2 per year by 2040?",
+                volume: None,
+            },
+            OgImageMarketSummary {
+                question: "This market is not shown on the card",
+                volume: Some(10),
+            },
+        ];
+        let data = OgImageCommunityPageData {
+            community: OgImageCommunityData::new("climate", &avatar).with_color("#ff4500"),
+            members: 48_210,
+            top_markets: &top_markets,
+        };
+
+        if let Some(image_data) = generate_image(data).await {
+            insta::assert_binary_snapshot!("community.png", image_data);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_og_image_comment_snapshot() {
+        let _guard = init_tracing();
+        let server = create_mock_avatar_server().await;
+        let author_avatar = format!("{}/test-avatar.png", server.url());
+        let community_avatar = format!("{}/test-avatar.jpg", server.url());
+
+        let data = OgImageCommentData {
+            author: OgImageAuthorData::new("skeptic", &author_avatar),
+            community: OgImageCommunityData::new("AI Predictions", &community_avatar),
+            question: "Will AI achieve AGI by 2030?",
+            body: "The benchmarks keep improving, but nobody agrees on what AGI even means. I would not bet on a resolution before the definition is settled.",
+            likes: 2_750,
+        };
+
+        if let Some(image_data) = generate_image(data).await {
+            insta::assert_binary_snapshot!("comment.png", image_data);
+        }
+    }
 }
@@ -0,0 +1,382 @@
+//! PNG optimization of generated images using oxipng.
+//!
+//! By default the external `oxipng` binary is used. With the `oxipng` cargo
+//! feature enabled, the `oxipng` crate is used in-process instead and no
+//! external binary is required.
+
+use crate::formatting::format_bytes;
+use crate::{OgImageGenerator, telemetry};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tracing::{debug, warn};
+
+/// Highest optimization level supported by oxipng.
+const MAX_LEVEL: u8 = 6;
+
+/// Which metadata chunks oxipng removes from the PNG.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StripMode {
+    /// Keep all metadata
+    None,
+    /// Remove all metadata that does not affect how the image is displayed
+    #[default]
+    Safe,
+    /// Remove all non-critical metadata
+    All,
+}
+
+/// Configuration of the PNG optimization step.
+///
+/// The default configuration uses optimization level 2 with
+/// [`StripMode::Safe`], which balances speed and compression.
+///
+/// # Examples
+///
+/// ```
+/// use crates_io_og_image::{OgImageGenerator, OptimizationConfig, StripMode};
+///
+/// let generator = OgImageGenerator::default().with_optimization(
+///     OptimizationConfig::level(4)
+///         .with_strip(StripMode::All)
+///         .with_zopfli(true),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptimizationConfig {
+    /// Whether the optimization step runs at all
+    pub enabled: bool,
+    /// Optimization level from 0 (fastest) to 6 (smallest); higher values are treated as 6
+    pub level: u8,
+    /// Which metadata chunks to remove
+    pub strip: StripMode,
+    /// Whether to use the much slower but stronger Zopfli compressor
+    pub zopfli: bool,
+}
+
+impl OptimizationConfig {
+    /// Creates a configuration that disables PNG optimization.
+    pub const fn off() -> Self {
+        Self {
+            enabled: false,
+            ..Self::level(2)
+        }
+    }
+
+    /// Creates a configuration with the given optimization level (0-6).
+    pub const fn level(level: u8) -> Self {
+        Self {
+            enabled: true,
+            level,
+            strip: StripMode::Safe,
+            zopfli: false,
+        }
+    }
+
+    /// Sets which metadata chunks to remove.
+    pub const fn with_strip(mut self, strip: StripMode) -> Self {
+        self.strip = strip;
+        self
+    }
+
+    /// Enables or disables the Zopfli compressor.
+    pub const fn with_zopfli(mut self, zopfli: bool) -> Self {
+        self.zopfli = zopfli;
+        self
+    }
+
+    /// Returns the optimization level, clamped to the supported range.
+    pub(crate) fn effective_level(&self) -> u8 {
+        self.level.min(MAX_LEVEL)
+    }
+}
+
+impl Default for OptimizationConfig {
+    fn default() -> Self {
+        Self::level(2)
+    }
+}
+
+/// Statistics about a successful PNG optimization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptimizationStats {
+    /// Size of the PNG before optimization, in bytes
+    pub original_size: u64,
+    /// Size of the PNG after optimization, in bytes
+    pub optimized_size: u64,
+    /// Time spent optimizing the PNG
+    pub duration: Duration,
+}
+
+impl OptimizationStats {
+    /// Returns the number of bytes saved by the optimization.
+    pub fn bytes_saved(&self) -> u64 {
+        self.original_size.saturating_sub(self.optimized_size)
+    }
+}
+
+impl OgImageGenerator {
+    /// Optimizes a PNG file in place according to the configured
+    /// [`OptimizationConfig`].
+    ///
+    /// This method attempts to reduce the file size of a PNG using lossless compression.
+    /// All errors are handled internally and logged as warnings. The method never fails
+    /// to ensure PNG optimization is truly optional. Returns `None` if the optimization
+    /// is disabled or failed.
+    pub(crate) async fn optimize_png(&self, png_file: &Path) -> Option<OptimizationStats> {
+        let config = &self.optimization;
+        if !config.enabled {
+            debug!("PNG optimization disabled, skipping");
+            return None;
+        }
+
+        debug!(
+            input_file = %png_file.display(),
+            level = config.effective_level(),
+            strip = ?config.strip,
+            zopfli = config.zopfli,
+            "Starting PNG optimization"
+        );
+
+        let original_size = fs::metadata(png_file).await.ok()?.len();
+        let start_time = Instant::now();
+
+        if !self.run_oxipng(png_file).await {
+            return None;
+        }
+
+        let duration = start_time.elapsed();
+        let optimized_size = fs::metadata(png_file).await.ok()?.len();
+
+        let stats = OptimizationStats {
+            original_size,
+            optimized_size,
+            duration,
+        };
+
+        telemetry::record_optimization_saved_bytes(stats.bytes_saved());
+
+        let bytes_saved = u32::try_from(stats.bytes_saved()).unwrap_or(u32::MAX);
+        debug!(
+            duration_ms = duration.as_millis(),
+            original_size,
+            optimized_size,
+            bytes_saved = %format_bytes(bytes_saved),
+            "PNG optimization completed successfully"
+        );
+
+        Some(stats)
+    }
+
+    /// Runs the external oxipng binary on the PNG file.
+    ///
+    /// Returns `true` if the optimization succeeded.
+    #[cfg(not(feature = "oxipng"))]
+    async fn run_oxipng(&self, png_file: &Path) -> bool {
+        use tokio::process::Command;
+
+        let config = &self.optimization;
+        let mut command = Command::new(&self.oxipng_binary_path);
+
+        command
+            .arg("--opt")
+            .arg(config.effective_level().to_string());
+
+        match config.strip {
+            StripMode::None => {}
+            StripMode::Safe => {
+                command.arg("--strip").arg("safe");
+            }
+            StripMode::All => {
+                command.arg("--strip").arg("all");
+            }
+        }
+
+        if config.zopfli {
+            command.arg("--zopfli");
+        }
+
+        // Overwrite the input PNG file
+        command.arg(png_file);
+
+        // Clear environment variables to avoid leaking sensitive data
+        command.env_clear();
+
+        // Preserve environment variables needed for running oxipng
+        if let Ok(path) = std::env::var("PATH") {
+            command.env("PATH", path);
+        }
+
+        match command.output().await {
+            Ok(output) if output.status.success() => true,
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                warn!(
+                    exit_code = ?output.status.code(),
+                    stderr = %stderr,
+                    stdout = %stdout,
+                    input_file = %png_file.display(),
+                    "PNG optimization failed, continuing with unoptimized image"
+                );
+                false
+            }
+            Err(err) => {
+                warn!(
+                    error = %err,
+                    input_file = %png_file.display(),
+                    oxipng_path = %self.oxipng_binary_path.display(),
+                    "Failed to execute oxipng, continuing with unoptimized image"
+                );
+                false
+            }
+        }
+    }
+
+    /// Runs the in-process oxipng optimizer on the PNG file.
+    ///
+    /// Returns `true` if the optimization succeeded.
+    #[cfg(feature = "oxipng")]
+    async fn run_oxipng(&self, png_file: &Path) -> bool {
+        use std::num::NonZeroU8;
+
+        let config = &self.optimization;
+        let mut options = oxipng::Options::from_preset(config.effective_level());
+
+        options.strip = match config.strip {
+            StripMode::None => oxipng::StripChunks::None,
+            StripMode::Safe => oxipng::StripChunks::Safe,
+            StripMode::All => oxipng::StripChunks::All,
+        };
+
+        if config.zopfli {
+            // Same number of iterations as the oxipng CLI default
+            let iterations = NonZeroU8::new(15).unwrap();
+            options.deflate = oxipng::Deflaters::Zopfli { iterations };
+        }
+
+        let input = match fs::read(png_file).await {
+            Ok(input) => input,
+            Err(err) => {
+                warn!(
+                    error = %err,
+                    input_file = %png_file.display(),
+                    "Failed to read PNG file, continuing with unoptimized image"
+                );
+                return false;
+            }
+        };
+
+        let result =
+            tokio::task::spawn_blocking(move || oxipng::optimize_from_memory(&input, &options))
+                .await;
+
+        let output = match result {
+            Ok(Ok(output)) => output,
+            Ok(Err(err)) => {
+                warn!(
+                    error = %err,
+                    input_file = %png_file.display(),
+                    "PNG optimization failed, continuing with unoptimized image"
+                );
+                return false;
+            }
+            Err(err) => {
+                warn!(
+                    error = %err,
+                    input_file = %png_file.display(),
+                    "PNG optimization task failed, continuing with unoptimized image"
+                );
+                return false;
+            }
+        };
+
+        if let Err(err) = fs::write(png_file, output).await {
+            warn!(
+                error = %err,
+                input_file = %png_file.display(),
+                "Failed to write optimized PNG file, continuing with unoptimized image"
+            );
+            return false;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optimization_config() {
+        let config = OptimizationConfig::default();
+        assert!(config.enabled);
+        assert_eq!(config.level, 2);
+        assert_eq!(config.strip, StripMode::Safe);
+        assert!(!config.zopfli);
+
+        assert!(!OptimizationConfig::off().enabled);
+        assert_eq!(OptimizationConfig::level(9).effective_level(), 6);
+    }
+
+    #[test]
+    fn test_bytes_saved() {
+        let stats = OptimizationStats {
+            original_size: 1000,
+            optimized_size: 600,
+            duration: Duration::from_millis(5),
+        };
+        assert_eq!(stats.bytes_saved(), 400);
+
+        let stats = OptimizationStats {
+            original_size: 600,
+            optimized_size: 1000,
+            duration: Duration::from_millis(5),
+        };
+        assert_eq!(stats.bytes_saved(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_optimize_png_disabled() {
+        let generator = OgImageGenerator::default().with_optimization(OptimizationConfig::off());
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            include_bytes!("../template/assets/fallback.png"),
+        )
+        .unwrap();
+
+        assert_eq!(generator.optimize_png(file.path()).await, None);
+    }
+
+    #[cfg(not(feature = "oxipng"))]
+    #[tokio::test]
+    async fn test_optimize_png_missing_binary() {
+        let generator = OgImageGenerator::default()
+            .with_oxipng_path(std::path::PathBuf::from("/nonexistent/oxipng"));
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let png = include_bytes!("../template/assets/fallback.png");
+        std::fs::write(file.path(), png).unwrap();
+
+        assert_eq!(generator.optimize_png(file.path()).await, None);
+        assert_eq!(std::fs::read(file.path()).unwrap(), png);
+    }
+
+    #[cfg(feature = "oxipng")]
+    #[tokio::test]
+    async fn test_optimize_png_in_process() {
+        let generator = OgImageGenerator::default()
+            .with_optimization(OptimizationConfig::level(1).with_strip(StripMode::All));
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let png = include_bytes!("../template/assets/test-avatar.png");
+        std::fs::write(file.path(), png).unwrap();
+
+        let stats = generator.optimize_png(file.path()).await.unwrap();
+        assert_eq!(stats.original_size, png.len() as u64);
+        assert_eq!(stats.optimized_size, file.path().metadata().unwrap().len());
+    }
+}
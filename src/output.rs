@@ -0,0 +1,233 @@
+//! Result types returned by [`OgImageGenerator::generate()`](crate::OgImageGenerator::generate).
+
+use crate::diagnostics::TypstDiagnostic;
+use crate::optimization::OptimizationStats;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tempfile::NamedTempFile;
+
+/// Image format of a generated OpenGraph image.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    /// Portable Network Graphics
+    #[default]
+    Png,
+    /// Scalable Vector Graphics
+    Svg,
+    /// Portable Document Format
+    Pdf,
+}
+
+impl ImageFormat {
+    /// Returns the file extension for this format, without a leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Svg => "svg",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    /// Returns the MIME type for this format, suitable for a `Content-Type` header.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Svg => "image/svg+xml",
+            Self::Pdf => "application/pdf",
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" => Ok(Self::Png),
+            "svg" => Ok(Self::Svg),
+            "pdf" => Ok(Self::Pdf),
+            _ => Err(format!(
+                "Unknown image format '{s}', expected 'png', 'svg' or 'pdf'"
+            )),
+        }
+    }
+}
+
+/// Durations of the individual stages of an OpenGraph image generation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OgImageTimings {
+    /// Time spent downloading avatars
+    pub avatars: Duration,
+    /// Time spent compiling the Typst template
+    pub compilation: Duration,
+    /// Time spent optimizing the PNG
+    pub optimization: Duration,
+    /// Total time spent generating the image
+    pub total: Duration,
+}
+
+/// Reason why an avatar was not included in the generated image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AvatarSkipReason {
+    /// The avatar URL returned `404 Not Found`
+    NotFound,
+    /// The avatar is not in a supported image format
+    UnsupportedFormat,
+    /// The download of an optional image, like a community banner, failed
+    DownloadFailed,
+}
+
+impl fmt::Display for AvatarSkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => f.write_str("not found"),
+            Self::UnsupportedFormat => f.write_str("unsupported format"),
+            Self::DownloadFailed => f.write_str("download failed"),
+        }
+    }
+}
+
+/// An avatar that was not included in the generated image.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedAvatar {
+    /// URL of the avatar
+    pub url: String,
+    /// Reason why the avatar was skipped
+    pub reason: AvatarSkipReason,
+}
+
+/// Result of a successful OpenGraph image generation
+#[derive(Debug)]
+pub struct OgImageOutput {
+    /// The generated image
+    pub image: NamedTempFile,
+    /// Format of the generated image
+    pub format: ImageFormat,
+    /// Width and height of the image in pixels, if they could be determined
+    pub dimensions: Option<(u32, u32)>,
+    /// Size of the image in bytes
+    pub size: u64,
+    /// Hex-encoded SHA-256 hash of the image contents
+    pub content_hash: String,
+    /// Durations of the individual generation stages
+    pub timings: OgImageTimings,
+    /// Number of avatars included in the image
+    pub avatar_count: usize,
+    /// Avatars that were not included in the image
+    pub skipped_avatars: Vec<SkippedAvatar>,
+    /// Warnings emitted by the Typst compiler
+    pub warnings: Vec<TypstDiagnostic>,
+    /// Whether `image` is a fallback image returned because rendering failed
+    pub degraded: bool,
+    /// Statistics about the PNG optimization, if it ran successfully
+    pub optimization: Option<OptimizationStats>,
+    /// Render cache key of the input data, or `None` for degraded images
+    pub cache_key: Option<String>,
+    /// Whether `image` was returned from the render cache instead of being
+    /// rendered. Cached images carry no avatar, warning or per-stage metadata.
+    pub cache_hit: bool,
+}
+
+impl OgImageOutput {
+    /// Returns a strong HTTP `ETag` value derived from the render cache key.
+    ///
+    /// Returns `None` for degraded images, which should not be cached
+    /// under the same tag as the real image.
+    pub fn etag(&self) -> Option<String> {
+        self.cache_key.as_ref().map(|key| format!("\"{key}\""))
+    }
+}
+
+/// Properties derived from the contents of a generated image.
+pub(crate) struct ImageInfo {
+    pub format: ImageFormat,
+    pub dimensions: Option<(u32, u32)>,
+    pub size: u64,
+    pub content_hash: String,
+}
+
+impl ImageInfo {
+    /// Determines the properties of an image from its contents.
+    ///
+    /// Dimensions are only determined for PNG images.
+    pub fn new(format: ImageFormat, bytes: &[u8]) -> Self {
+        let dimensions = match format {
+            ImageFormat::Png => png_dimensions(bytes),
+            ImageFormat::Svg | ImageFormat::Pdf => None,
+        };
+
+        Self {
+            format,
+            dimensions,
+            size: bytes.len() as u64,
+            content_hash: content_hash(bytes),
+        }
+    }
+}
+
+/// Reads the width and height from the `IHDR` chunk of a PNG image.
+fn png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    const SIGNATURE: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+    // The IHDR chunk always comes first: length (4), type (4), width (4), height (4)
+    let header = bytes.strip_prefix(SIGNATURE)?;
+    if header.get(4..8)? != b"IHDR" {
+        return None;
+    }
+
+    let width = u32::from_be_bytes(header.get(8..12)?.try_into().ok()?);
+    let height = u32::from_be_bytes(header.get(12..16)?.try_into().ok()?);
+    Some((width, height))
+}
+
+/// Returns the hex-encoded SHA-256 hash of the given bytes.
+pub(crate) fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png_dimensions() {
+        let fallback = include_bytes!("../template/assets/fallback.png");
+        assert_eq!(png_dimensions(fallback), Some((1200, 630)));
+
+        let jpeg = include_bytes!("../template/assets/test-avatar.jpg");
+        assert_eq!(png_dimensions(jpeg), None);
+        assert_eq!(png_dimensions(&[]), None);
+        assert_eq!(png_dimensions(&fallback[..12]), None);
+    }
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(content_hash(b"og-image").len(), 64);
+    }
+
+    #[test]
+    fn test_image_format() {
+        assert_eq!(ImageFormat::Png.extension(), "png");
+        assert_eq!(ImageFormat::Png.mime_type(), "image/png");
+        assert_eq!(ImageFormat::Png.to_string(), "png");
+        assert_eq!(ImageFormat::Svg.mime_type(), "image/svg+xml");
+        assert_eq!(ImageFormat::Pdf.extension(), "pdf");
+        assert_eq!("svg".parse::<ImageFormat>(), Ok(ImageFormat::Svg));
+        assert!("gif".parse::<ImageFormat>().is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Owned, deserializable counterparts of the borrowed [`OgImageData`] types.
+//!
+//! These are useful when the data is received as JSON, e.g. by an HTTP
+//! service or read from a file.
+
+use crate::{
+    NumberFormat, OgImageAuthorData, OgImageCommunityData, OgImageData, OgImageDataPoint,
+    OgImageGraphData, OgImageMarketKind, OgImageScalarData,
+};
+use serde::{Deserialize, Serialize};
+
+/// Owned form of [`OgImageData`].
+///
+/// # Examples
+///
+/// ```
+/// use crates_io_og_image::OwnedOgImageData;
+///
+/// let json = r#"{
+///     "question": "Will it rain tomorrow?",
+///     "author": { "name": "user", "avatar": "https://example.com/user.png" },
+///     "community": { "handle": "weather", "avatar": "https://example.com/weather.png" },
+///     "outcome": "NONE",
+///     "graph": []
+/// }"#;
+///
+/// let owned: OwnedOgImageData = serde_json::from_str(json)?;
+/// let graph = owned.graph();
+/// let data = owned.as_data(&graph);
+/// assert_eq!(data.question, "Will it rain tomorrow?");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnedOgImageData {
+    /// The prediction market question
+    pub question: String,
+    /// Author information
+    pub author: OwnedOgImageAuthorData,
+    /// Community information
+    pub community: OwnedOgImageCommunityData,
+    /// Current outcome status
+    pub outcome: String,
+    /// Kind of market, determining how the values of the graph are shown
+    #[serde(default)]
+    pub kind: OwnedOgImageMarketKind,
+    /// Graph data containing outcome-specific order history
+    #[serde(default)]
+    pub graph: Vec<OwnedOgImageGraphData>,
+    /// Unix timestamp at which the market closes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closes_at: Option<u64>,
+    /// Unix timestamp at which the market was resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<u64>,
+}
+
+impl OwnedOgImageData {
+    /// Returns the borrowed graph data, to be passed to [`as_data()`](Self::as_data).
+    pub fn graph(&self) -> Vec<OgImageGraphData<'_>> {
+        self.graph
+            .iter()
+            .map(OwnedOgImageGraphData::as_data)
+            .collect()
+    }
+
+    /// Returns the borrowed form of the data, using the graph data returned
+    /// by [`graph()`](Self::graph).
+    pub fn as_data<'a>(&'a self, graph: &'a [OgImageGraphData<'a>]) -> OgImageData<'a> {
+        OgImageData {
+            question: &self.question,
+            author: OgImageAuthorData::new(&self.author.name, &self.author.avatar),
+            community: OgImageCommunityData {
+                banner: self.community.banner.as_deref(),
+                color: self.community.color.as_deref(),
+                ..OgImageCommunityData::new(&self.community.handle, &self.community.avatar)
+            },
+            outcome: &self.outcome,
+            kind: self.kind.as_data(),
+            graph,
+            closes_at: self.closes_at,
+            resolved_at: self.resolved_at,
+        }
+    }
+}
+
+/// Owned form of [`OgImageAuthorData`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedOgImageAuthorData {
+    /// Author username/name
+    pub name: String,
+    /// Avatar URL
+    pub avatar: String,
+}
+
+/// Owned form of [`OgImageCommunityData`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedOgImageCommunityData {
+    /// Community handle
+    pub handle: String,
+    /// Community avatar URL
+    pub avatar: String,
+    /// Community banner image URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub banner: Option<String>,
+    /// Community brand color as hex code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+/// Owned form of [`OgImageMarketKind`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OwnedOgImageMarketKind {
+    /// Outcomes with a probability between 0 and 100 percent
+    #[default]
+    Probability,
+    /// A numeric value within a range
+    Scalar(OwnedOgImageScalarData),
+}
+
+impl OwnedOgImageMarketKind {
+    /// Returns the borrowed form of the market kind.
+    pub fn as_data(&self) -> OgImageMarketKind<'_> {
+        match self {
+            Self::Probability => OgImageMarketKind::Probability,
+            Self::Scalar(scalar) => OgImageMarketKind::Scalar(OgImageScalarData {
+                min: scalar.min,
+                max: scalar.max,
+                unit: &scalar.unit,
+                format: scalar.format,
+                decimals: scalar.decimals,
+            }),
+        }
+    }
+}
+
+/// Owned form of [`OgImageScalarData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnedOgImageScalarData {
+    /// Lowest value of the range
+    pub min: f64,
+    /// Highest value of the range
+    pub max: f64,
+    /// Unit shown after the values, may be empty
+    #[serde(default)]
+    pub unit: String,
+    /// Format of the values
+    #[serde(default)]
+    pub format: NumberFormat,
+    /// Number of decimal places of the values
+    #[serde(default)]
+    pub decimals: u8,
+}
+
+/// Owned form of [`OgImageGraphData`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnedOgImageGraphData {
+    /// Outcome identifier (e.g., "Yes", "No")
+    pub outcome: String,
+    /// Color hex code for this outcome
+    pub color: String,
+    /// Historical order data points for this outcome
+    pub data: Vec<OgImageDataPoint>,
+}
+
+impl OwnedOgImageGraphData {
+    /// Returns the borrowed form of the graph data.
+    pub fn as_data(&self) -> OgImageGraphData<'_> {
+        OgImageGraphData {
+            outcome: &self.outcome,
+            color: &self.color,
+            data: &self.data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owned_data_roundtrip() {
+        let json = r##"{
+            "question": "Will this test pass?",
+            "author": { "name": "user", "avatar": "https://example.com/user.png" },
+            "community": {
+                "handle": "test",
+                "avatar": "https://example.com/test.png",
+                "banner": "https://example.com/banner.png",
+                "color": "#ff4500"
+            },
+            "outcome": "NONE",
+            "closes_at": 1745229600,
+            "graph": [
+                {
+                    "outcome": "Yes",
+                    "color": "#00613F",
+                    "data": [{ "time": 1744249342, "value": 50 }, { "time": 1744249396, "value": 60 }]
+                }
+            ]
+        }"##;
+
+        let owned: OwnedOgImageData = serde_json::from_str(json).unwrap();
+
+        let graph = owned.graph();
+        let data = owned.as_data(&graph);
+        assert_eq!(data.graph.len(), owned.graph.len());
+
+        // The borrowed form serializes to the same JSON as the owned form
+        let borrowed_json = serde_json::to_value(&data).unwrap();
+        assert_eq!(borrowed_json, serde_json::to_value(&owned).unwrap());
+    }
+
+    #[test]
+    fn test_owned_data_without_graph() {
+        let json = r#"{
+            "question": "Question?",
+            "author": { "name": "user", "avatar": "https://example.com/user.png" },
+            "community": { "handle": "test", "avatar": "https://example.com/test.png" },
+            "outcome": "NONE"
+        }"#;
+
+        let owned: OwnedOgImageData = serde_json::from_str(json).unwrap();
+        assert!(owned.graph.is_empty());
+        assert_eq!(owned.kind, OwnedOgImageMarketKind::Probability);
+    }
+
+    #[test]
+    fn test_owned_scalar_market() {
+        let json = r#"{
+            "question": "What will BTC close at?",
+            "author": { "name": "user", "avatar": "https://example.com/user.png" },
+            "community": { "handle": "crypto", "avatar": "https://example.com/crypto.png" },
+            "outcome": "NONE",
+            "kind": { "type": "scalar", "min": 50000, "max": 80000, "unit": "USD", "format": "compact" }
+        }"#;
+
+        let owned: OwnedOgImageData = serde_json::from_str(json).unwrap();
+        let graph = owned.graph();
+        let data = owned.as_data(&graph);
+        let expected =
+            OgImageScalarData::new(50000., 80000., "USD").with_format(NumberFormat::Compact);
+        assert_eq!(data.kind, OgImageMarketKind::Scalar(expected));
+        assert_eq!(data.kind.range(), (50000., 80000.));
+        assert_eq!(expected.format_value(64250.4), "64K USD");
+
+        let temperature = OgImageScalarData::new(-20., 40., "°C").with_decimals(1);
+        assert_eq!(temperature.format_value(-3.25), "-3.2 °C");
+        assert_eq!(temperature.format_value(21.5), "21.5 °C");
+        let compact = temperature.with_format(NumberFormat::Compact);
+        assert_eq!(compact.format_value(-1234.56), "-1,234.6 °C");
+        assert_eq!(compact.format_value(-64250.), "-64K °C");
+    }
+}
@@ -0,0 +1,400 @@
+//! Startup capability checks for the external binaries and fonts used by
+//! [`OgImageGenerator`].
+//!
+//! Missing or incompatible binaries are otherwise only discovered at the first
+//! call to [`OgImageGenerator::generate()`]. Running
+//! [`OgImageGenerator::self_check()`] at startup surfaces these problems early.
+
+use crate::OgImageGenerator;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use tokio::process::Command;
+use tracing::{debug, info, instrument, warn};
+
+/// Font family required by the bundled template.
+pub const REQUIRED_FONT_FAMILY: &str = "IBM Plex Sans";
+
+/// Supported Typst versions (`>=0.13.0, <0.14.0`).
+pub const SUPPORTED_TYPST_VERSIONS: VersionRange =
+    VersionRange::new(Version::new(0, 13, 0), Version::new(0, 14, 0));
+
+/// Supported oxipng versions (`>=9.0.0, <10.0.0`).
+pub const SUPPORTED_OXIPNG_VERSIONS: VersionRange =
+    VersionRange::new(Version::new(9, 0, 0), Version::new(10, 0, 0));
+
+/// A `major.minor.patch` version number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    /// Creates a new `Version` from its components.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Extracts the first `major.minor.patch` version number from the output
+    /// of a `--version` command, e.g. `typst 0.13.1 (8ace67d9)`.
+    pub fn parse_from_output(output: &str) -> Option<Self> {
+        output.split_whitespace().find_map(|word| {
+            let word = word.trim_start_matches('v');
+            let mut parts = word.splitn(3, '.');
+            let major = parts.next()?.parse().ok()?;
+            let minor = parts.next()?.parse().ok()?;
+
+            // Ignore pre-release or build suffixes like `1.2.3-rc.1`
+            let patch = parts.next()?;
+            let patch = patch.split(|c: char| !c.is_ascii_digit()).next()?;
+            let patch = patch.parse().ok()?;
+
+            Some(Self::new(major, minor, patch))
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A half-open range of versions (`>=min, <max`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionRange {
+    /// Minimum supported version (inclusive)
+    pub min: Version,
+    /// Maximum supported version (exclusive)
+    pub max: Version,
+}
+
+impl VersionRange {
+    /// Creates a new `VersionRange` from `min` (inclusive) to `max` (exclusive).
+    pub const fn new(min: Version, max: Version) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns `true` if the version lies within this range.
+    pub fn contains(&self, version: &Version) -> bool {
+        *version >= self.min && *version < self.max
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ">={}, <{}", self.min, self.max)
+    }
+}
+
+/// Outcome of checking an external binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryStatus {
+    /// The binary was found and its version is supported
+    Supported(Version),
+    /// The binary was found but its version is outside the supported range
+    Unsupported(Version),
+    /// The binary was found but its version could not be determined
+    UnknownVersion { output: String },
+    /// The binary was executed but exited with an error
+    Failed {
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    /// The binary could not be executed
+    Missing { error: String },
+}
+
+/// Result of checking a single external binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryCheck {
+    /// Path of the binary that was checked
+    pub path: PathBuf,
+    /// Range of versions supported by this crate
+    pub supported: VersionRange,
+    /// Outcome of the check
+    pub status: BinaryStatus,
+}
+
+impl BinaryCheck {
+    /// Returns `true` if the binary was found and its version is supported.
+    pub fn is_ok(&self) -> bool {
+        matches!(self.status, BinaryStatus::Supported(_))
+    }
+}
+
+/// Outcome of checking for the required font family.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontStatus {
+    /// The font family is available to Typst
+    Available,
+    /// Typst ran successfully, but the font family is not available
+    Missing,
+    /// The available fonts could not be listed
+    Unchecked { error: String },
+}
+
+/// Result of checking for the font family required by the template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontCheck {
+    /// Name of the required font family
+    pub family: &'static str,
+    /// Custom font path passed to Typst, if any
+    pub font_path: Option<PathBuf>,
+    /// Outcome of the check
+    pub status: FontStatus,
+}
+
+impl FontCheck {
+    /// Returns `true` if the font family is available to Typst.
+    pub fn is_ok(&self) -> bool {
+        self.status == FontStatus::Available
+    }
+}
+
+/// Structured report returned by [`OgImageGenerator::self_check()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfCheckReport {
+    /// Result of checking the Typst binary
+    pub typst: BinaryCheck,
+    /// Result of checking the oxipng binary, or `None` if the in-process
+    /// optimizer of the `oxipng` cargo feature is used instead
+    pub oxipng: Option<BinaryCheck>,
+    /// Result of checking the font required by the template
+    pub font: FontCheck,
+}
+
+impl SelfCheckReport {
+    /// Returns `true` if all checks passed.
+    pub fn is_ok(&self) -> bool {
+        let oxipng_ok = self.oxipng.as_ref().is_none_or(BinaryCheck::is_ok);
+        self.typst.is_ok() && oxipng_ok && self.font.is_ok()
+    }
+}
+
+impl OgImageGenerator {
+    /// Checks that the configured binaries and fonts are usable.
+    ///
+    /// Runs `typst --version` and `oxipng --version`, compares the reported
+    /// versions against [`SUPPORTED_TYPST_VERSIONS`] and
+    /// [`SUPPORTED_OXIPNG_VERSIONS`], and verifies that the
+    /// [`REQUIRED_FONT_FAMILY`] is available to Typst. This method never
+    /// fails; all problems are reported in the returned [`SelfCheckReport`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crates_io_og_image::OgImageGenerator;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), crates_io_og_image::OgImageError> {
+    /// let generator = OgImageGenerator::from_environment()?;
+    /// let report = generator.self_check().await;
+    /// if !report.is_ok() {
+    ///     eprintln!("OpenGraph image generation is unavailable: {report:?}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn self_check(&self) -> SelfCheckReport {
+        let typst = check_binary(&self.typst_binary_path, SUPPORTED_TYPST_VERSIONS).await;
+        let oxipng = if cfg!(feature = "oxipng") {
+            None
+        } else {
+            Some(check_binary(&self.oxipng_binary_path, SUPPORTED_OXIPNG_VERSIONS).await)
+        };
+        let font = self.check_font().await;
+
+        let report = SelfCheckReport {
+            typst,
+            oxipng,
+            font,
+        };
+
+        if report.is_ok() {
+            info!("Self-check completed successfully");
+        } else {
+            warn!(?report, "Self-check found problems");
+        }
+
+        report
+    }
+
+    /// Checks whether the [`REQUIRED_FONT_FAMILY`] is available to Typst.
+    async fn check_font(&self) -> FontCheck {
+        let mut command = Command::new(&self.typst_binary_path);
+        command.arg("fonts");
+
+        // Use the same font discovery as `generate()`
+        if let Some(font_path) = &self.typst_font_path {
+            command.arg("--font-path").arg(font_path);
+            command.arg("--ignore-system-fonts");
+        }
+
+        let status = match run(command).await {
+            Ok(output) if output.status.success() => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let available = stdout
+                    .lines()
+                    .any(|family| family.trim().eq_ignore_ascii_case(REQUIRED_FONT_FAMILY));
+
+                if available {
+                    FontStatus::Available
+                } else {
+                    FontStatus::Missing
+                }
+            }
+            Ok(output) => FontStatus::Unchecked {
+                error: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            },
+            Err(err) => FontStatus::Unchecked {
+                error: err.to_string(),
+            },
+        };
+
+        debug!(
+            family = REQUIRED_FONT_FAMILY,
+            ?status,
+            "Font check completed"
+        );
+
+        FontCheck {
+            family: REQUIRED_FONT_FAMILY,
+            font_path: self.typst_font_path.clone(),
+            status,
+        }
+    }
+}
+
+/// Runs `<path> --version` and compares the result against `supported`.
+async fn check_binary(path: &Path, supported: VersionRange) -> BinaryCheck {
+    let mut command = Command::new(path);
+    command.arg("--version");
+
+    let status = match run(command).await {
+        // A broken binary may still print a version, e.g. without its libraries
+        Ok(output) if !output.status.success() => BinaryStatus::Failed {
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        },
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            match Version::parse_from_output(&stdout) {
+                Some(version) if supported.contains(&version) => BinaryStatus::Supported(version),
+                Some(version) => BinaryStatus::Unsupported(version),
+                None => BinaryStatus::UnknownVersion {
+                    output: stdout.trim().to_string(),
+                },
+            }
+        }
+        Err(err) => BinaryStatus::Missing {
+            error: err.to_string(),
+        },
+    };
+
+    debug!(path = %path.display(), %supported, ?status, "Binary check completed");
+
+    BinaryCheck {
+        path: path.to_path_buf(),
+        supported,
+        status,
+    }
+}
+
+/// Runs a command with a cleared environment, preserving only the variables
+/// needed for binary and font discovery.
+async fn run(mut command: Command) -> std::io::Result<Output> {
+    command.env_clear();
+
+    if let Ok(path) = std::env::var("PATH") {
+        command.env("PATH", path);
+    }
+    if let Ok(home) = std::env::var("HOME") {
+        command.env("HOME", home);
+    }
+
+    command.output().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            Version::parse_from_output("typst 0.13.1 (8ace67d9)"),
+            Some(Version::new(0, 13, 1))
+        );
+        assert_eq!(
+            Version::parse_from_output("oxipng 9.1.5\n"),
+            Some(Version::new(9, 1, 5))
+        );
+        assert_eq!(
+            Version::parse_from_output("tool v1.2.3-rc.1"),
+            Some(Version::new(1, 2, 3))
+        );
+        assert_eq!(Version::parse_from_output("typst"), None);
+        assert_eq!(Version::parse_from_output("version 1.2"), None);
+    }
+
+    #[test]
+    fn test_supported_versions() {
+        assert!(SUPPORTED_TYPST_VERSIONS.contains(&Version::new(0, 13, 0)));
+        assert!(SUPPORTED_TYPST_VERSIONS.contains(&Version::new(0, 13, 1)));
+        assert!(!SUPPORTED_TYPST_VERSIONS.contains(&Version::new(0, 12, 0)));
+        assert!(!SUPPORTED_TYPST_VERSIONS.contains(&Version::new(0, 14, 0)));
+
+        assert!(SUPPORTED_OXIPNG_VERSIONS.contains(&Version::new(9, 1, 5)));
+        assert!(!SUPPORTED_OXIPNG_VERSIONS.contains(&Version::new(8, 0, 0)));
+        assert!(!SUPPORTED_OXIPNG_VERSIONS.contains(&Version::new(10, 0, 0)));
+
+        assert_eq!(SUPPORTED_TYPST_VERSIONS.to_string(), ">=0.13.0, <0.14.0");
+    }
+
+    #[tokio::test]
+    async fn test_self_check_missing_binaries() {
+        let generator = OgImageGenerator::default()
+            .with_typst_path(PathBuf::from("/nonexistent/typst"))
+            .with_oxipng_path(PathBuf::from("/nonexistent/oxipng"));
+
+        let report = generator.self_check().await;
+
+        assert!(!report.is_ok());
+        assert!(matches!(report.typst.status, BinaryStatus::Missing { .. }));
+        if let Some(oxipng) = report.oxipng {
+            assert!(matches!(oxipng.status, BinaryStatus::Missing { .. }));
+        }
+        assert!(matches!(report.font.status, FontStatus::Unchecked { .. }));
+        assert_eq!(report.font.family, REQUIRED_FONT_FAMILY);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_check_failing_binary() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("typst");
+        let script = "#!/bin/sh\necho 'typst 0.13.1'\necho 'library not found' >&2\nexit 127\n";
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let check = check_binary(&path, SUPPORTED_TYPST_VERSIONS).await;
+        assert!(!check.is_ok());
+        assert_eq!(
+            check.status,
+            BinaryStatus::Failed {
+                exit_code: Some(127),
+                stderr: "library not found".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Publishing generated OpenGraph images to a storage backend.
+//!
+//! An [`ImageStore`] receives the generated images under deterministic object
+//! keys derived from the [render cache key](OgImageGenerator::cache_key), so
+//! identical input data always maps to the same object. This allows
+//! [`OgImageGenerator::publish()`] to skip rendering entirely if the object
+//! already exists in the store.
+
+use crate::{OgImageData, OgImageError, OgImageGenerator, OgImageOutput};
+use async_trait::async_trait;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+use tracing::{debug, info, instrument, warn};
+
+/// Default `Cache-Control` header for published images.
+///
+/// Object keys change whenever the rendered image would change, so the
+/// objects can be cached forever.
+pub const DEFAULT_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// Storage backend for published images.
+#[async_trait]
+pub trait ImageStore: Send + Sync {
+    /// Returns `true` if an object with the given key exists.
+    async fn exists(&self, key: &str) -> Result<bool, OgImageError>;
+
+    /// Stores an object under the given key, replacing any existing object.
+    async fn put(
+        &self,
+        key: &str,
+        body: &[u8],
+        content_type: &str,
+        cache_control: &str,
+    ) -> Result<(), OgImageError>;
+}
+
+/// [`ImageStore`] writing objects as files below a root directory.
+///
+/// Object keys are used as relative paths, and missing parent directories are
+/// created. The `Content-Type` and `Cache-Control` values are not stored; they
+/// are expected to be set by the web server serving the directory.
+#[derive(Debug, Clone)]
+pub struct FilesystemImageStore {
+    root: PathBuf,
+}
+
+impl FilesystemImageStore {
+    /// Creates a store writing objects below the given root directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, OgImageError> {
+        let relative = Path::new(key);
+        let is_valid = !key.is_empty()
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+
+        if !is_valid {
+            let message = "Object key must be a relative path without `..` components";
+            let source = io::Error::new(io::ErrorKind::InvalidInput, message);
+            let key = key.to_string();
+            return Err(OgImageError::StoreIoError { key, source });
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl ImageStore for FilesystemImageStore {
+    async fn exists(&self, key: &str) -> Result<bool, OgImageError> {
+        let path = self.path(key)?;
+        fs::try_exists(&path)
+            .await
+            .map_err(|source| OgImageError::StoreIoError {
+                key: key.to_string(),
+                source,
+            })
+    }
+
+    async fn put(
+        &self,
+        key: &str,
+        body: &[u8],
+        _content_type: &str,
+        _cache_control: &str,
+    ) -> Result<(), OgImageError> {
+        let path = self.path(key)?;
+        let map_err = |source| OgImageError::StoreIoError {
+            key: key.to_string(),
+            source,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(map_err)?;
+        }
+
+        fs::write(&path, body).await.map_err(map_err)
+    }
+}
+
+/// Options for [`OgImageGenerator::publish()`].
+///
+/// # Examples
+///
+/// ```
+/// use crates_io_og_image::PublishOptions;
+///
+/// let options = PublishOptions::default()
+///     .with_prefix("og-images/")
+///     .with_skip_existing(true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishOptions {
+    /// Prefix prepended to every object key, e.g. `"og-images/"`
+    pub prefix: String,
+    /// Value of the `Cache-Control` header of published objects
+    pub cache_control: String,
+    /// Whether to skip rendering if the object already exists in the store
+    pub skip_existing: bool,
+}
+
+impl PublishOptions {
+    /// Sets the prefix prepended to every object key.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets the `Cache-Control` header of published objects.
+    pub fn with_cache_control(mut self, cache_control: impl Into<String>) -> Self {
+        self.cache_control = cache_control.into();
+        self
+    }
+
+    /// Sets whether to skip rendering if the object already exists.
+    pub fn with_skip_existing(mut self, skip_existing: bool) -> Self {
+        self.skip_existing = skip_existing;
+        self
+    }
+}
+
+impl Default for PublishOptions {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            cache_control: DEFAULT_CACHE_CONTROL.to_string(),
+            skip_existing: false,
+        }
+    }
+}
+
+/// Result of [`OgImageGenerator::publish()`].
+#[derive(Debug)]
+pub struct PublishedImage {
+    /// Object key of the image in the store
+    pub key: String,
+    /// What happened to the image
+    pub outcome: PublishOutcome,
+}
+
+/// What [`OgImageGenerator::publish()`] did with an image.
+#[derive(Debug)]
+pub enum PublishOutcome {
+    /// The image was rendered and uploaded to the store
+    Uploaded(OgImageOutput),
+    /// The object already existed, so nothing was rendered or uploaded
+    AlreadyExists,
+    /// Rendering failed and a fallback image was returned, which was not
+    /// uploaded to avoid storing it under the key of the real image
+    Degraded(OgImageOutput),
+}
+
+impl OgImageGenerator {
+    /// Returns the object key under which the image for `data` is published.
+    ///
+    /// The key is `{prefix}{cache_key}.{extension}`, using the
+    /// [`cache_key()`](Self::cache_key) of the data and the extension of the
+    /// configured output format.
+    pub fn object_key(
+        &self,
+        data: &OgImageData<'_>,
+        options: &PublishOptions,
+    ) -> Result<String, OgImageError> {
+        let cache_key = self.cache_key(data)?;
+        let extension = self.format.extension();
+        Ok(format!("{}{cache_key}.{extension}", options.prefix))
+    }
+
+    /// Generates an OpenGraph image and uploads it to the given store.
+    ///
+    /// The image is stored under its [`object_key()`](Self::object_key) with
+    /// the `Content-Type` of the image format and the configured
+    /// `Cache-Control` header. If [`PublishOptions::skip_existing`] is set and
+    /// the object already exists, rendering is skipped entirely.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crates_io_og_image::{FilesystemImageStore, OgImageAuthorData, OgImageCommunityData, OgImageData, OgImageError, OgImageGenerator, OgImageMarketKind, PublishOptions};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), OgImageError> {
+    /// let generator = OgImageGenerator::default();
+    /// let store = FilesystemImageStore::new("/var/www/og-images");
+    /// let data = OgImageData {
+    ///     question: "Will AI solve climate change by 2030?",
+    ///     author: OgImageAuthorData::new("user", "https://example.com/avatar.png"),
+    ///     community: OgImageCommunityData::new("climate", "https://example.com/community.png"),
+    ///     outcome: "NONE",
+    ///     kind: OgImageMarketKind::Probability,
+    ///     graph: &[],
+    ///     closes_at: None,
+    ///     resolved_at: None,
+    /// };
+    /// let options = PublishOptions::default().with_skip_existing(true);
+    /// let published = generator.publish(data, &store, &options).await?;
+    /// println!("Published image as: {}", published.key);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip_all, fields(question = %data.question))]
+    pub async fn publish(
+        &self,
+        data: OgImageData<'_>,
+        store: &dyn ImageStore,
+        options: &PublishOptions,
+    ) -> Result<PublishedImage, OgImageError> {
+        let key = self.object_key(&data, options)?;
+
+        if options.skip_existing && store.exists(&key).await? {
+            info!(key = %key, "Image already exists in store, skipping rendering");
+            let outcome = PublishOutcome::AlreadyExists;
+            return Ok(PublishedImage { key, outcome });
+        }
+
+        let output = self.generate(data).await?;
+        if output.degraded {
+            warn!(key = %key, "Not uploading degraded fallback image");
+            let outcome = PublishOutcome::Degraded(output);
+            return Ok(PublishedImage { key, outcome });
+        }
+
+        let body = fs::read(output.image.path()).await?;
+        let content_type = output.format.mime_type();
+
+        debug!(key = %key, size_bytes = body.len(), "Uploading image to store");
+        store
+            .put(&key, &body, content_type, &options.cache_control)
+            .await?;
+
+        info!(key = %key, "Image published successfully");
+        let outcome = PublishOutcome::Uploaded(output);
+        Ok(PublishedImage { key, outcome })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OgImageMarketKind;
+
+    #[tokio::test]
+    async fn test_filesystem_image_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = FilesystemImageStore::new(temp_dir.path());
+
+        assert!(!store.exists("og/abc.png").await.unwrap());
+
+        store
+            .put("og/abc.png", b"image", "image/png", DEFAULT_CACHE_CONTROL)
+            .await
+            .unwrap();
+
+        assert!(store.exists("og/abc.png").await.unwrap());
+        let path = temp_dir.path().join("og/abc.png");
+        assert_eq!(std::fs::read(path).unwrap(), b"image");
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_image_store_invalid_keys() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = FilesystemImageStore::new(temp_dir.path());
+
+        for key in ["", "../abc.png", "og/../../abc.png", "/abc.png"] {
+            let result = store.put(key, b"image", "image/png", "").await;
+            assert!(
+                matches!(result, Err(OgImageError::StoreIoError { .. })),
+                "{key:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_object_key() {
+        let generator = OgImageGenerator::default();
+        let data = OgImageData {
+            question: "Question?",
+            author: crate::OgImageAuthorData::new("user", "https://example.com/user.png"),
+            community: crate::OgImageCommunityData::new("test", "https://example.com/test.png"),
+            outcome: "NONE",
+            kind: OgImageMarketKind::Probability,
+            graph: &[],
+            closes_at: None,
+            resolved_at: None,
+        };
+
+        let cache_key = generator.cache_key(&data).unwrap();
+        let options = PublishOptions::default().with_prefix("og/");
+        let key = generator.object_key(&data, &options).unwrap();
+        assert_eq!(key, format!("og/{cache_key}.png"));
+    }
+}
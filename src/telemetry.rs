@@ -0,0 +1,162 @@
+//! Metrics for the OpenGraph image generation pipeline.
+//!
+//! With the `metrics` cargo feature enabled, counters and histograms are
+//! recorded through the [`metrics`](https://docs.rs/metrics) facade, so any
+//! exporter (e.g. Prometheus or StatsD) can be installed by the application.
+//! Without the feature, all recording functions are no-ops.
+
+use std::time::Duration;
+
+/// Counter of image generations, labeled by `result` (`success`, `fallback` or `error`).
+pub const GENERATIONS_TOTAL: &str = "og_image_generations_total";
+
+/// Counter of avatar downloads, labeled by `outcome` (`ok`, `404`, `unsupported` or `error`).
+pub const AVATAR_DOWNLOADS_TOTAL: &str = "og_image_avatar_downloads_total";
+
+/// Counter of render cache lookups, labeled by `result` (`hit` or `miss`).
+pub const RENDER_CACHE_LOOKUPS_TOTAL: &str = "og_image_render_cache_lookups_total";
+
+/// Histogram of Typst compilation durations in seconds.
+pub const COMPILE_DURATION_SECONDS: &str = "og_image_compile_duration_seconds";
+
+/// Histogram of bytes saved by the PNG optimization.
+pub const OPTIMIZATION_SAVED_BYTES: &str = "og_image_optimization_saved_bytes";
+
+/// Histogram of generated image sizes in bytes.
+pub const OUTPUT_SIZE_BYTES: &str = "og_image_output_size_bytes";
+
+/// Result of a call to [`OgImageGenerator::generate()`](crate::OgImageGenerator::generate).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GenerationResult {
+    Success,
+    Fallback,
+    Error,
+}
+
+impl GenerationResult {
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Fallback => "fallback",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Outcome of a single avatar download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AvatarDownloadOutcome {
+    Ok,
+    NotFound,
+    Unsupported,
+    Error,
+}
+
+impl AvatarDownloadOutcome {
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::NotFound => "404",
+            Self::Unsupported => "unsupported",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Result of a render cache lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CacheLookupResult {
+    Hit,
+    Miss,
+}
+
+impl CacheLookupResult {
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Hit => "hit",
+            Self::Miss => "miss",
+        }
+    }
+}
+
+/// Registers descriptions and units for all metrics with the installed recorder.
+///
+/// Calling this function is optional, but gives exporters like Prometheus
+/// the information needed to emit `HELP` lines and units.
+pub fn describe_metrics() {
+    #[cfg(feature = "metrics")]
+    {
+        use metrics::{Unit, describe_counter, describe_histogram};
+
+        describe_counter!(GENERATIONS_TOTAL, "Number of OpenGraph image generations");
+        describe_counter!(AVATAR_DOWNLOADS_TOTAL, "Number of avatar downloads");
+        describe_counter!(RENDER_CACHE_LOOKUPS_TOTAL, "Number of render cache lookups");
+        describe_histogram!(
+            COMPILE_DURATION_SECONDS,
+            Unit::Seconds,
+            "Duration of the Typst compilation"
+        );
+        describe_histogram!(
+            OPTIMIZATION_SAVED_BYTES,
+            Unit::Bytes,
+            "Bytes saved by the PNG optimization"
+        );
+        describe_histogram!(
+            OUTPUT_SIZE_BYTES,
+            Unit::Bytes,
+            "Size of the generated images"
+        );
+    }
+}
+
+#[cfg(feature = "metrics")]
+mod imp {
+    use super::*;
+    use metrics::{counter, histogram};
+
+    pub fn record_generation(result: GenerationResult) {
+        counter!(GENERATIONS_TOTAL, "result" => result.as_str()).increment(1);
+    }
+
+    pub fn record_avatar_download(outcome: AvatarDownloadOutcome) {
+        counter!(AVATAR_DOWNLOADS_TOTAL, "outcome" => outcome.as_str()).increment(1);
+    }
+
+    pub fn record_cache_lookup(result: CacheLookupResult) {
+        counter!(RENDER_CACHE_LOOKUPS_TOTAL, "result" => result.as_str()).increment(1);
+    }
+
+    pub fn record_compile_duration(duration: Duration) {
+        histogram!(COMPILE_DURATION_SECONDS).record(duration);
+    }
+
+    pub fn record_optimization_saved_bytes(bytes: u64) {
+        histogram!(OPTIMIZATION_SAVED_BYTES).record(bytes as f64);
+    }
+
+    pub fn record_output_size(bytes: u64) {
+        histogram!(OUTPUT_SIZE_BYTES).record(bytes as f64);
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod imp {
+    use super::*;
+
+    pub fn record_generation(_result: GenerationResult) {}
+
+    pub fn record_avatar_download(_outcome: AvatarDownloadOutcome) {}
+
+    pub fn record_cache_lookup(_result: CacheLookupResult) {}
+
+    pub fn record_compile_duration(_duration: Duration) {}
+
+    pub fn record_optimization_saved_bytes(_bytes: u64) {}
+
+    pub fn record_output_size(_bytes: u64) {}
+}
+
+pub(crate) use imp::*;
@@ -0,0 +1,524 @@
+//! Typst templates used to render OpenGraph images.
+//!
+//! A [`Template`] consists of a main `.typ` source, a set of named assets that
+//! are written next to it, and an [`InputSchema`] declaring which fields of
+//! the image data the template requires. The [builtin market
+//! template](Template::market) is used by default, and every other
+//! [`CardKind`] has a builtin template as well, see
+//! [`Template::builtin_for()`]. Other templates can be loaded from a directory
+//! with [`Template::from_dir()`].
+//!
+//! Templates read their data from JSON files written to the template root:
+//!
+//! - `data.json`: the serialized card data, like
+//!   [`OgImageData`](crate::OgImageData) for market cards
+//! - `avatar_map.json`: object mapping avatar URLs to the downloaded files
+//!   in the `assets` directory, named `author_avatar.*`,
+//!   `community_avatar.*`, `community_banner.*` and `profile_avatar.*`
+//! - `chart.json`: the value axis, formatted current values, date labels
+//!   and gridlines of the chart, only written for market templates, see
+//!   [`ChartOptions`](crate::ChartOptions)
+//!
+//! These file names are reserved and can't be used by template files.
+//!
+//! The remaining options are passed through `sys.inputs`:
+//!
+//! - `layout`: the name of the configured [`Layout`](crate::Layout)
+//! - `theme`: the JSON-serialized [`Theme`](crate::Theme)
+//! - `timing`: the closing or resolution time of market cards, like
+//!   "Ends in 3d", only passed if the market has one
+
+use crate::{CardKind, OgImageError};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// File name of the manifest of a template directory.
+pub const TEMPLATE_MANIFEST: &str = "template.json";
+
+/// Template input file with the JSON-serialized card data.
+pub(crate) const DATA_FILE: &str = "data.json";
+/// Template input file mapping avatar URLs to the downloaded files.
+pub(crate) const AVATAR_MAP_FILE: &str = "avatar_map.json";
+/// Template input file with the chart options of market cards.
+pub(crate) const CHART_FILE: &str = "chart.json";
+
+/// Paths written by the generator, which would overwrite template files.
+const RESERVED_PATHS: &[&str] = &[DATA_FILE, AVATAR_MAP_FILE, CHART_FILE];
+
+/// A Typst template with its assets and input schema.
+///
+/// # Examples
+///
+/// ```
+/// use crates_io_og_image::{InputSchema, Template};
+///
+/// let source = b"#set page(width: 600pt, height: 315pt)\n#json(\"data.json\").question";
+/// let template = Template::new("card", "card.typ", source.as_slice())
+///     .with_asset("assets/logo.svg", b"<svg/>".as_slice())
+///     .with_schema(InputSchema::new(["question"]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    name: String,
+    main_path: String,
+    main: Cow<'static, [u8]>,
+    assets: BTreeMap<String, Cow<'static, [u8]>>,
+    schema: InputSchema,
+}
+
+/// Fields of the image data a [`Template`] requires.
+///
+/// Before rendering, the serialized image data is checked for the required
+/// top-level fields, so a template never has to handle missing fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct InputSchema {
+    /// Top-level fields of the image data that must be present and not `null`
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+}
+
+impl InputSchema {
+    /// Creates a schema requiring the given fields.
+    pub fn new<I, S>(required_fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let required_fields = required_fields.into_iter().map(Into::into).collect();
+        Self { required_fields }
+    }
+
+    /// Checks that the serialized image data contains all required fields.
+    pub(crate) fn validate(
+        &self,
+        template: &str,
+        data: &serde_json::Value,
+    ) -> Result<(), OgImageError> {
+        let missing = self
+            .required_fields
+            .iter()
+            .find(|field| data.get(field.as_str()).is_none_or(|value| value.is_null()));
+
+        match missing {
+            Some(field) => Err(OgImageError::MissingTemplateInput {
+                template: template.to_string(),
+                field: field.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Manifest of a template directory, see [`Template::from_dir()`].
+#[derive(Debug, Deserialize)]
+struct TemplateManifest {
+    name: String,
+    main: String,
+    #[serde(default)]
+    schema: InputSchema,
+}
+
+impl Template {
+    /// Creates a template from its name, the relative path and the source of
+    /// its main `.typ` file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `main_path` is not a relative path without `..` components,
+    /// or one of the reserved input files like `data.json`.
+    pub fn new(
+        name: impl Into<String>,
+        main_path: impl Into<String>,
+        main: impl Into<Cow<'static, [u8]>>,
+    ) -> Self {
+        let main_path = main_path.into();
+        assert!(
+            is_valid_path(&main_path),
+            "invalid template path {main_path:?}"
+        );
+        assert!(
+            !is_reserved_path(&main_path),
+            "reserved template path {main_path:?}"
+        );
+
+        Self {
+            name: name.into(),
+            main_path,
+            main: main.into(),
+            assets: BTreeMap::new(),
+            schema: InputSchema::default(),
+        }
+    }
+
+    /// Returns the builtin prediction market template.
+    pub fn market() -> Self {
+        const MAIN: &[u8] = include_bytes!("../template/og-image.typ");
+        let schema = InputSchema::new(["question", "author", "community", "outcome", "graph"]);
+        Self::builtin("market", "og-image.typ", MAIN, schema)
+    }
+
+    /// Returns the builtin user profile template.
+    pub fn profile() -> Self {
+        const MAIN: &[u8] = include_bytes!("../template/profile.typ");
+        let schema = InputSchema::new(["name", "avatar", "predictions", "profit_loss"]);
+        Self::builtin("profile", "profile.typ", MAIN, schema)
+    }
+
+    /// Returns the builtin community page template.
+    pub fn community() -> Self {
+        const MAIN: &[u8] = include_bytes!("../template/community.typ");
+        let schema = InputSchema::new(["community", "members", "top_markets"]);
+        Self::builtin("community", "community.typ", MAIN, schema)
+    }
+
+    /// Returns the builtin comment share template.
+    pub fn comment() -> Self {
+        const MAIN: &[u8] = include_bytes!("../template/comment.typ");
+        let schema = InputSchema::new(["author", "community", "question", "body", "likes"]);
+        Self::builtin("comment", "comment.typ", MAIN, schema)
+    }
+
+    /// Returns the builtin template for the given card kind.
+    pub fn builtin_for(kind: CardKind) -> Self {
+        match kind {
+            CardKind::Market => Self::market(),
+            CardKind::Profile => Self::profile(),
+            CardKind::Community => Self::community(),
+            CardKind::Comment => Self::comment(),
+        }
+    }
+
+    /// Creates a builtin template with the shared components and assets.
+    fn builtin(name: &str, main_path: &str, main: &'static [u8], schema: InputSchema) -> Self {
+        const SHARED: &[(&str, &[u8])] = &[
+            ("common.typ", include_bytes!("../template/common.typ")),
+            (
+                "assets/inertia.svg",
+                include_bytes!("../template/assets/inertia.svg"),
+            ),
+            (
+                "assets/likes.svg",
+                include_bytes!("../template/assets/likes.svg"),
+            ),
+            (
+                "assets/og-template.svg",
+                include_bytes!("../template/assets/og-template.svg"),
+            ),
+            (
+                "assets/og-template-light.svg",
+                include_bytes!("../template/assets/og-template-light.svg"),
+            ),
+            (
+                "assets/volume.svg",
+                include_bytes!("../template/assets/volume.svg"),
+            ),
+        ];
+
+        let template = Self::new(name, main_path, main).with_schema(schema);
+        SHARED.iter().fold(template, |template, (path, contents)| {
+            template.with_asset(*path, *contents)
+        })
+    }
+
+    /// Loads a template from a directory.
+    ///
+    /// The directory must contain a `template.json` manifest with the name of
+    /// the template, the path of its main `.typ` file and optionally the
+    /// input schema:
+    ///
+    /// ```json
+    /// {
+    ///     "name": "my-card",
+    ///     "main": "card.typ",
+    ///     "schema": { "required_fields": ["question", "author"] }
+    /// }
+    /// ```
+    ///
+    /// All other files in the directory and its subdirectories, except for
+    /// hidden files, are loaded as assets under their relative paths. The
+    /// directory must not contain the reserved input files `data.json`,
+    /// `avatar_map.json` and `chart.json`, which are written by the generator.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, OgImageError> {
+        let dir = dir.as_ref();
+
+        let manifest_path = dir.join(TEMPLATE_MANIFEST);
+        let manifest = read(&manifest_path)?;
+        let manifest: TemplateManifest = serde_json::from_slice(&manifest).map_err(|err| {
+            let source = io::Error::new(io::ErrorKind::InvalidData, err);
+            let path = manifest_path.clone();
+            OgImageError::TemplateLoadError { path, source }
+        })?;
+
+        if !is_valid_path(&manifest.main) {
+            let message = "Main file must be a relative path without `..` components";
+            let source = io::Error::new(io::ErrorKind::InvalidInput, message);
+            let path = manifest_path;
+            return Err(OgImageError::TemplateLoadError { path, source });
+        }
+
+        if is_reserved_path(&manifest.main) {
+            let message = format!("`{}` is reserved for the template input", manifest.main);
+            let source = io::Error::new(io::ErrorKind::InvalidInput, message);
+            let path = manifest_path;
+            return Err(OgImageError::TemplateLoadError { path, source });
+        }
+
+        let main = read(&dir.join(&manifest.main))?;
+        let mut template =
+            Self::new(manifest.name, manifest.main, main).with_schema(manifest.schema);
+
+        let mut files = Vec::new();
+        collect_files(dir, Path::new(""), &mut files)?;
+        for relative in files {
+            // Asset paths use `/` as separator on all platforms, like Typst
+            let components = relative.components();
+            let components = components.map(|component| component.as_os_str().to_string_lossy());
+            let path = components.collect::<Vec<_>>().join("/");
+
+            if is_reserved_path(&path) {
+                let message = format!("`{path}` is reserved for the template input");
+                let source = io::Error::new(io::ErrorKind::InvalidInput, message);
+                let path = dir.join(relative);
+                return Err(OgImageError::TemplateLoadError { path, source });
+            }
+
+            if path != TEMPLATE_MANIFEST && path != template.main_path {
+                let contents = read(&dir.join(&relative))?;
+                template.assets.insert(path, contents.into());
+            }
+        }
+
+        Ok(template)
+    }
+
+    /// Adds an asset, written to the given path relative to the main file.
+    ///
+    /// The generator writes the input files `data.json`, `avatar_map.json`
+    /// and `chart.json` to the template root, and the avatars to
+    /// `assets/author_avatar.*`, `assets/community_avatar.*`,
+    /// `assets/community_banner.*` and `assets/profile_avatar.*`, so these
+    /// paths must not be used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is not a relative path without `..` components, or
+    /// one of the reserved input files.
+    pub fn with_asset(
+        mut self,
+        path: impl Into<String>,
+        contents: impl Into<Cow<'static, [u8]>>,
+    ) -> Self {
+        let path = path.into();
+        assert!(is_valid_path(&path), "invalid template path {path:?}");
+        assert!(!is_reserved_path(&path), "reserved template path {path:?}");
+        self.assets.insert(path, contents.into());
+        self
+    }
+
+    /// Sets the input schema of the template.
+    pub fn with_schema(mut self, schema: InputSchema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Returns the name of the template.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the input schema of the template.
+    pub fn schema(&self) -> &InputSchema {
+        &self.schema
+    }
+
+    /// Returns the path of the main file, relative to the template root.
+    pub(crate) fn main_path(&self) -> &str {
+        &self.main_path
+    }
+
+    /// Returns all files of the template, starting with the main file,
+    /// followed by the assets ordered by path.
+    pub(crate) fn files(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        let main = (self.main_path.as_str(), self.main.as_ref());
+        let assets = self.assets.iter();
+        let assets = assets.map(|(path, contents)| (path.as_str(), contents.as_ref()));
+        std::iter::once(main).chain(assets)
+    }
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Self::market()
+    }
+}
+
+/// Returns `true` if `path` is a non-empty relative path without `..`
+/// components.
+fn is_valid_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Returns `true` if `path` is one of the input files written by the
+/// generator.
+fn is_reserved_path(path: &str) -> bool {
+    RESERVED_PATHS.contains(&path)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, OgImageError> {
+    std::fs::read(path).map_err(|source| OgImageError::TemplateLoadError {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Collects the relative paths of all non-hidden files below `root`.
+fn collect_files(
+    root: &Path,
+    relative: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), OgImageError> {
+    let dir = root.join(relative);
+    let map_err = |source| OgImageError::TemplateLoadError {
+        path: dir.clone(),
+        source,
+    };
+
+    for entry in std::fs::read_dir(&dir).map_err(map_err)? {
+        let entry = entry.map_err(map_err)?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let path = relative.join(entry.file_name());
+        if entry.file_type().map_err(map_err)?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_market_template() {
+        let template = Template::market();
+        assert_eq!(template.name(), "market");
+
+        let paths = template.files().map(|(path, _)| path).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "og-image.typ",
+                "assets/inertia.svg",
+                "assets/likes.svg",
+                "assets/og-template-light.svg",
+                "assets/og-template.svg",
+                "assets/volume.svg",
+                "common.typ",
+            ]
+        );
+
+        let template = Template::builtin_for(CardKind::Comment);
+        assert_eq!(template.name(), "comment");
+        assert_eq!(template.main_path(), "comment.typ");
+    }
+
+    #[test]
+    fn test_template_from_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+
+        let manifest = r#"{
+            "name": "card",
+            "main": "card.typ",
+            "schema": { "required_fields": ["question"] }
+        }"#;
+        std::fs::write(dir.join(TEMPLATE_MANIFEST), manifest).unwrap();
+        std::fs::write(dir.join("card.typ"), r#"#json("data.json").question"#).unwrap();
+        std::fs::create_dir_all(dir.join("assets/icons")).unwrap();
+        std::fs::write(dir.join("assets/icons/logo.svg"), "<svg/>").unwrap();
+        std::fs::write(dir.join(".DS_Store"), "").unwrap();
+
+        let template = Template::from_dir(dir).unwrap();
+        let main = br#"#json("data.json").question"#;
+        let expected = Template::new("card", "card.typ", main.as_slice())
+            .with_asset("assets/icons/logo.svg", b"<svg/>".as_slice())
+            .with_schema(InputSchema::new(["question"]));
+        assert_eq!(template, expected);
+    }
+
+    #[test]
+    fn test_template_from_dir_errors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+
+        // Missing manifest
+        let result = Template::from_dir(dir);
+        assert!(matches!(
+            result,
+            Err(OgImageError::TemplateLoadError { .. })
+        ));
+
+        // Main file outside of the template directory
+        let manifest = r#"{ "name": "card", "main": "../card.typ" }"#;
+        std::fs::write(dir.join(TEMPLATE_MANIFEST), manifest).unwrap();
+        let result = Template::from_dir(dir);
+        assert!(matches!(
+            result,
+            Err(OgImageError::TemplateLoadError { .. })
+        ));
+
+        // Main file that would be overwritten by the template input
+        let manifest = r#"{ "name": "card", "main": "data.json" }"#;
+        std::fs::write(dir.join(TEMPLATE_MANIFEST), manifest).unwrap();
+        let Err(OgImageError::TemplateLoadError { source, .. }) = Template::from_dir(dir) else {
+            panic!("Expected a template load error");
+        };
+        assert_eq!(
+            source.to_string(),
+            "`data.json` is reserved for the template input"
+        );
+
+        // File that would be overwritten by the template input
+        let manifest = r#"{ "name": "card", "main": "card.typ" }"#;
+        std::fs::write(dir.join(TEMPLATE_MANIFEST), manifest).unwrap();
+        std::fs::write(dir.join("card.typ"), "").unwrap();
+        std::fs::write(dir.join("chart.json"), "{}").unwrap();
+        let result = Template::from_dir(dir);
+        assert!(matches!(
+            result,
+            Err(OgImageError::TemplateLoadError { path, .. }) if path.ends_with("chart.json")
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "reserved template path")]
+    fn test_template_reserved_asset() {
+        let _ = Template::market().with_asset("data.json", b"{}".as_slice());
+    }
+
+    #[test]
+    fn test_input_schema_validation() {
+        let schema = InputSchema::new(["question", "graph"]);
+
+        let data = serde_json::json!({ "question": "Question?", "graph": [] });
+        assert!(schema.validate("market", &data).is_ok());
+
+        let data = serde_json::json!({ "question": "Question?", "graph": null });
+        let result = schema.validate("market", &data);
+        assert!(
+            matches!(result, Err(OgImageError::MissingTemplateInput { field, .. }) if field == "graph")
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Color themes of the OpenGraph image template.
+
+use serde::{Deserialize, Serialize};
+
+/// Colors and branding of the rendered images.
+///
+/// The theme is serialized as JSON into the `theme` input of the template.
+/// Colors are hex strings like `"#00613f"`, as accepted by Typst's `rgb()`.
+/// Themes loaded from JSON can be checked with [`validate()`](Self::validate).
+///
+/// # Examples
+///
+/// ```
+/// use crates_io_og_image::{OgImageGenerator, Theme};
+///
+/// let theme = Theme {
+///     accent: "#ffb000".to_string(),
+///     ..Theme::dark()
+/// };
+/// let generator = OgImageGenerator::default().with_theme(theme);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    /// Stops of the diagonal background gradient, from the top left to the
+    /// bottom right corner. A single stop results in a solid background.
+    pub background: Vec<String>,
+    /// Color of the question
+    pub accent: String,
+    /// Color of regular text
+    pub text: String,
+    /// Color of secondary text, like the author and community line
+    pub text_light: String,
+    /// Path of the header logo, relative to the template root
+    pub logo: String,
+    /// Color of the logo watermark in the bottom right corner
+    pub watermark: String,
+    /// Opacity of the watermark, from `0.0` (invisible) to `1.0` (opaque)
+    pub watermark_opacity: f32,
+}
+
+impl Theme {
+    /// Returns the dark green theme, used by default.
+    pub fn dark() -> Self {
+        Self {
+            background: to_strings(&[
+                "#00613f", "#003321", "#001911", "#001911", "#001911", "#00613f",
+            ]),
+            accent: "#29ffb4".to_string(),
+            text: "#e5fff6".to_string(),
+            text_light: "#ccffed".to_string(),
+            logo: "assets/og-template.svg".to_string(),
+            watermark: "#00613f".to_string(),
+            watermark_opacity: 0.3,
+        }
+    }
+
+    /// Returns a light theme with dark text on a pale green background.
+    pub fn light() -> Self {
+        Self {
+            background: to_strings(&["#ffffff", "#f2fbf7", "#e3f5ec", "#e3f5ec", "#ffffff"]),
+            accent: "#00613f".to_string(),
+            text: "#0f2a20".to_string(),
+            text_light: "#2d4a3f".to_string(),
+            logo: "assets/og-template-light.svg".to_string(),
+            watermark: "#00613f".to_string(),
+            watermark_opacity: 0.1,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_json() {
+        let json = serde_json::to_value(Theme::light()).unwrap();
+        assert_eq!(json["accent"], "#00613f");
+        assert_eq!(json["text_light"], "#2d4a3f");
+        assert_eq!(json["logo"], "assets/og-template-light.svg");
+
+        let theme: Theme = serde_json::from_value(json).unwrap();
+        assert_eq!(theme, Theme::light());
+    }
+}
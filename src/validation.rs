@@ -0,0 +1,434 @@
+//! Validation of the image data before rendering.
+//!
+//! Invalid data would otherwise fail inside the Typst template, with errors
+//! that are hard to relate to the input, e.g. when a series of the graph has
+//! no data points or the community color is not a hex color.
+//! [`OgImageCard::validate()`] reports all invalid fields at once, and is
+//! called automatically by [`OgImageGenerator::generate()`](crate::OgImageGenerator::generate),
+//! even if a fallback image is configured.
+
+use crate::chart::is_hex_color;
+use crate::formatting::MAX_DECIMALS;
+use crate::{
+    OgImageCard, OgImageCommentData, OgImageCommunityData, OgImageCommunityPageData, OgImageData,
+    OgImageError, OgImageMarketKind, Theme,
+};
+use std::fmt;
+
+/// An invalid field of the image data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// Path of the field, like `graph[0].data`
+    pub field: String,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Collects the invalid fields of the image data.
+#[derive(Default)]
+struct FieldErrors(Vec<FieldError>);
+
+impl FieldErrors {
+    fn push(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    fn require_non_empty(&mut self, field: &str, value: &str) {
+        if value.trim().is_empty() {
+            self.push(field, "must not be empty");
+        }
+    }
+
+    fn community(&mut self, community: &OgImageCommunityData<'_>) {
+        self.require_non_empty("community.handle", community.handle);
+        self.require_non_empty("community.avatar", community.avatar);
+        if community.color.is_some_and(|color| !is_hex_color(color)) {
+            self.push("community.color", "must be a hex color like #ff4500");
+        }
+    }
+
+    fn finish(self) -> Result<(), OgImageError> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(OgImageError::InvalidInput(self.0)),
+        }
+    }
+}
+
+impl OgImageCard<'_> {
+    /// Checks that the card can be rendered by its template.
+    ///
+    /// Markets are checked with [`OgImageData::validate()`], and the
+    /// community of community page and comment cards needs a handle, an
+    /// avatar URL and, if set, a hex color. Profile cards have no
+    /// requirements.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OgImageError::InvalidInput`] with all invalid fields.
+    pub fn validate(&self) -> Result<(), OgImageError> {
+        match self {
+            OgImageCard::Market(data) => data.validate(),
+            OgImageCard::Profile(_) => Ok(()),
+            OgImageCard::Community(data) => data.validate(),
+            OgImageCard::Comment(data) => data.validate(),
+        }
+    }
+}
+
+impl OgImageCommunityPageData<'_> {
+    /// Checks that the community has a handle, an avatar URL and, if set, a
+    /// hex color.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OgImageError::InvalidInput`] with all invalid fields.
+    pub fn validate(&self) -> Result<(), OgImageError> {
+        let mut errors = FieldErrors::default();
+        errors.community(&self.community);
+        errors.finish()
+    }
+}
+
+impl OgImageCommentData<'_> {
+    /// Checks that the community of the comment has a handle, an avatar URL
+    /// and, if set, a hex color.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OgImageError::InvalidInput`] with all invalid fields.
+    pub fn validate(&self) -> Result<(), OgImageError> {
+        let mut errors = FieldErrors::default();
+        errors.community(&self.community);
+        errors.finish()
+    }
+}
+
+impl OgImageData<'_> {
+    /// Checks that the data can be rendered by the template.
+    ///
+    /// The question, names, handles, avatar URLs and outcomes must not be
+    /// empty, the community color must be a hex color, every series of the
+    /// graph needs at least one data point, and all values must be finite
+    /// numbers within the range of the [market kind](OgImageMarketKind).
+    /// The range of scalar markets must be finite and not empty.
+    /// Only the first invalid value of each series is reported. An empty
+    /// graph is valid, and rendered without a chart.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OgImageError::InvalidInput`] with all invalid fields.
+    pub fn validate(&self) -> Result<(), OgImageError> {
+        let mut errors = FieldErrors::default();
+
+        errors.require_non_empty("question", self.question);
+        errors.require_non_empty("author.name", self.author.name);
+        errors.require_non_empty("author.avatar", self.author.avatar);
+        errors.community(&self.community);
+
+        let (min, max) = self.kind.range();
+        if let OgImageMarketKind::Scalar(scalar) = self.kind {
+            if !min.is_finite() {
+                errors.push("kind.min", "must be a finite number");
+            }
+            if !max.is_finite() {
+                errors.push("kind.max", "must be a finite number");
+            } else if min >= max {
+                errors.push("kind.max", "must be greater than min");
+            }
+            if scalar.decimals > MAX_DECIMALS {
+                errors.push("kind.decimals", format!("must be at most {MAX_DECIMALS}"));
+            }
+        }
+
+        for (index, series) in self.graph.iter().enumerate() {
+            errors.require_non_empty(&format!("graph[{index}].outcome"), series.outcome);
+            if series.data.is_empty() {
+                errors.push(
+                    format!("graph[{index}].data"),
+                    "must contain at least one data point",
+                );
+            }
+
+            let range = min..=max;
+            let invalid = series.data.iter().enumerate().find_map(|(point, data)| {
+                let message = match data.value {
+                    value if !value.is_finite() => "must be a finite number".to_string(),
+                    value if !range.contains(&value) => format!("must be between {min} and {max}"),
+                    _ => return None,
+                };
+                Some((point, message))
+            });
+            if let Some((point, message)) = invalid {
+                errors.push(format!("graph[{index}].data[{point}].value"), message);
+            }
+        }
+
+        errors.finish()
+    }
+}
+
+impl Theme {
+    /// Checks that all colors of the theme are hex colors like `#00613f`,
+    /// that the background has at least one stop, and that the watermark
+    /// opacity is between `0.0` and `1.0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OgImageError::InvalidInput`] with all invalid fields.
+    pub fn validate(&self) -> Result<(), OgImageError> {
+        let mut errors = FieldErrors::default();
+
+        if self.background.is_empty() {
+            errors.push("background", "must contain at least one color");
+        }
+        let background = self.background.iter().enumerate();
+        let background = background.map(|(index, color)| (format!("background[{index}]"), color));
+        let colors = [
+            ("accent", &self.accent),
+            ("text", &self.text),
+            ("text_light", &self.text_light),
+            ("watermark", &self.watermark),
+        ];
+        let colors = colors.map(|(field, color)| (field.to_string(), color));
+        for (field, color) in background.chain(colors) {
+            if !is_hex_color(color) {
+                errors.push(field, "must be a hex color like #00613f");
+            }
+        }
+
+        if !(0.0..=1.0).contains(&self.watermark_opacity) {
+            errors.push("watermark_opacity", "must be between 0 and 1");
+        }
+
+        errors.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        OgImageAuthorData, OgImageCommunityData, OgImageDataPoint, OgImageGraphData,
+        OgImageScalarData,
+    };
+
+    fn test_data<'a>(graph: &'a [OgImageGraphData<'a>]) -> OgImageData<'a> {
+        OgImageData {
+            question: "Will this test pass?",
+            author: OgImageAuthorData::new("user", "https://example.com/user.png"),
+            community: OgImageCommunityData::new("test", "https://example.com/test.png"),
+            outcome: "NONE",
+            kind: OgImageMarketKind::Probability,
+            graph,
+            closes_at: None,
+            resolved_at: None,
+        }
+    }
+
+    fn invalid_fields(data: &OgImageData<'_>) -> Vec<String> {
+        match data.validate() {
+            Ok(()) => Vec::new(),
+            Err(OgImageError::InvalidInput(errors)) => {
+                errors.into_iter().map(|error| error.to_string()).collect()
+            }
+            Err(err) => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let points = [
+            OgImageDataPoint {
+                time: 1,
+                value: 0.4,
+            },
+            OgImageDataPoint {
+                time: 2,
+                value: 100.,
+            },
+        ];
+        let graph = [OgImageGraphData {
+            outcome: "Yes",
+            color: "",
+            data: &points,
+        }];
+        assert!(test_data(&graph).validate().is_ok());
+        assert!(test_data(&[]).validate().is_ok());
+
+        let invalid_points = [
+            OgImageDataPoint {
+                time: 1,
+                value: f64::NAN,
+            },
+            OgImageDataPoint {
+                time: 2,
+                value: 101.,
+            },
+        ];
+        let graph = [
+            OgImageGraphData {
+                outcome: " ",
+                color: "#00f29c",
+                data: &[],
+            },
+            OgImageGraphData {
+                outcome: "No",
+                color: "#00f29c",
+                data: &invalid_points,
+            },
+        ];
+        let data = OgImageData {
+            question: "",
+            community: OgImageCommunityData::new("test", "https://example.com/test.png")
+                .with_color("orange"),
+            ..test_data(&graph)
+        };
+        assert_eq!(
+            invalid_fields(&data),
+            [
+                "question: must not be empty",
+                "community.color: must be a hex color like #ff4500",
+                "graph[0].outcome: must not be empty",
+                "graph[0].data: must contain at least one data point",
+                "graph[1].data[0].value: must be a finite number",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_community_cards() {
+        let community = OgImageCommunityData::new("test", "https://example.com/test.png");
+        let page = OgImageCommunityPageData {
+            community: community.clone().with_color("#ff4500"),
+            members: 1,
+            top_markets: &[],
+        };
+        assert!(OgImageCard::from(page.clone()).validate().is_ok());
+
+        let page = OgImageCommunityPageData {
+            community: community.with_color("orange"),
+            ..page
+        };
+        let comment = OgImageCommentData {
+            author: OgImageAuthorData::new("user", "https://example.com/user.png"),
+            community: OgImageCommunityData::new("", "https://example.com/test.png"),
+            question: "Will this test pass?",
+            body: "Yes",
+            likes: 0,
+        };
+        for (card, expected) in [
+            (
+                OgImageCard::from(page),
+                "community.color: must be a hex color like #ff4500",
+            ),
+            (
+                OgImageCard::from(comment),
+                "community.handle: must not be empty",
+            ),
+        ] {
+            let Err(OgImageError::InvalidInput(errors)) = card.validate() else {
+                panic!("{card:?} should be invalid");
+            };
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_validate_scalar() {
+        let points = [OgImageDataPoint {
+            time: 1,
+            value: 90000.,
+        }];
+        let graph = [OgImageGraphData {
+            outcome: "BTC",
+            color: "#f7931a",
+            data: &points,
+        }];
+
+        let scalar = OgImageScalarData::new(50000., 100000., "USD");
+        let data = OgImageData {
+            kind: OgImageMarketKind::Scalar(scalar),
+            ..test_data(&graph)
+        };
+        assert!(data.validate().is_ok());
+
+        let scalar = OgImageScalarData::new(50000., 80000., "USD");
+        let data = OgImageData {
+            kind: OgImageMarketKind::Scalar(scalar),
+            ..test_data(&graph)
+        };
+        assert_eq!(
+            invalid_fields(&data),
+            ["graph[0].data[0].value: must be between 50000 and 80000"]
+        );
+
+        let scalar = OgImageScalarData::new(80000., 80000., "USD");
+        let data = OgImageData {
+            kind: OgImageMarketKind::Scalar(scalar),
+            ..test_data(&[])
+        };
+        assert_eq!(
+            invalid_fields(&data),
+            ["kind.max: must be greater than min"]
+        );
+
+        let scalar = OgImageScalarData {
+            min: f64::NEG_INFINITY,
+            decimals: 5,
+            ..OgImageScalarData::new(-20., 40., "°C")
+        };
+        let data = OgImageData {
+            kind: OgImageMarketKind::Scalar(scalar),
+            ..test_data(&[])
+        };
+        assert_eq!(
+            invalid_fields(&data),
+            [
+                "kind.min: must be a finite number",
+                "kind.decimals: must be at most 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_theme() {
+        assert!(Theme::dark().validate().is_ok());
+        assert!(Theme::light().validate().is_ok());
+
+        let theme = Theme {
+            background: vec!["#00613f".to_string(), "00613f".to_string()],
+            accent: "#ffb00".to_string(),
+            watermark_opacity: 1.5,
+            ..Theme::dark()
+        };
+        let Err(OgImageError::InvalidInput(errors)) = theme.validate() else {
+            panic!("expected invalid theme");
+        };
+        let errors = errors.iter().map(FieldError::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "background[1]: must be a hex color like #00613f",
+                "accent: must be a hex color like #00613f",
+                "watermark_opacity: must be between 0 and 1",
+            ]
+        );
+
+        let theme = Theme {
+            background: Vec::new(),
+            ..Theme::light()
+        };
+        assert!(theme.validate().is_err());
+    }
+}
@@ -0,0 +1,46 @@
+// =============================================================================
+// Inertia OG image template: comment
+// =============================================================================
+// This template generates Open Graph images for comments on prediction markets.
+
+// =============================================================================
+// IMPORTS
+// =============================================================================
+
+#import "common.typ": *
+
+// =============================================================================
+// DATA LOADING
+// =============================================================================
+
+#let data = json("data.json")
+
+// =============================================================================
+// MAIN DOCUMENT
+// =============================================================================
+
+#show: card-page.with(community: data.community)
+
+// Main content area
+#place(
+    left + top,
+    dy: 40pt,
+    block(height: 100% - header-height, inset: 35pt, clip: false, {
+        // Author and community
+        set text(size: 15pt, fill: colors.text-light)
+        let author = (name: data.author.name, avatar: asset-path(data.author.avatar))
+        let community = (handle: data.community.handle, avatar: asset-path(data.community.avatar))
+        block(render-author-community(author, community))
+
+        // Comment
+        block(above: 16pt, inset: (left: 14pt), stroke: (left: 3pt + colors.primary),
+            text(size: 20pt, fill: colors.text, truncate_to_height(data.body, maxHeight: 80pt))
+        )
+
+        // Market and likes
+        block(above: 16pt, grid(columns: (1fr, auto), column-gutter: 16pt, align: horizon,
+            truncate_to_width([on *#data.question*]),
+            render-metadata(data.likes, "likes"),
+        ))
+    })
+)
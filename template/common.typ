@@ -0,0 +1,268 @@
+// =============================================================================
+// Inertia OG image template: shared components
+// =============================================================================
+// Theme, layout and components shared by the templates of all card kinds.
+// Import everything with `#import "common.typ": *` and wrap the card with
+// `#show: card-page.with(..)`.
+
+// =============================================================================
+// COLOR PALETTE
+// =============================================================================
+
+// Theme passed in by the generator, see `Theme::dark()` for the defaults
+#let default-theme = (
+    background: ("#00613f", "#003321", "#001911", "#001911", "#001911", "#00613f"),
+    accent: "#29ffb4",
+    text: "#e5fff6",
+    text_light: "#ccffed",
+    logo: "assets/og-template.svg",
+    watermark: "#00613f",
+    watermark_opacity: 0.3,
+)
+#let theme = if "theme" in sys.inputs { json(bytes(sys.inputs.theme)) } else { default-theme }
+
+#let background = if theme.background.len() == 1 {
+    rgb(theme.background.at(0))
+} else {
+    gradient.linear(..theme.background.map(rgb), angle: 45deg)
+}
+
+#let colors = (
+    bg: background,
+    logo-overlay: rgb(theme.watermark).transparentize(100% - theme.watermark_opacity * 100%),
+    header-text: oklch(100%, 0, 0deg),
+    primary: rgb(theme.accent),
+    yes: rgb(0, 242, 156),
+    no: oklch(63.68%, 0.152, 25.2deg),
+    text: rgb(theme.text),
+    text-light: rgb(theme.text_light),
+    avatar-bg: oklch(100%, 0, 0deg),
+    avatar-border: oklch(87%, 0.01, 98deg),
+)
+
+// =============================================================================
+// LAYOUT CONSTANTS
+// =============================================================================
+
+#let header-height = 80pt
+
+// Page sizes and chart heights (in canvas units) of the supported layouts
+#let layouts = (
+    opengraph: (width: 600pt, height: 315pt, chart-height: 3.5),
+    square: (width: 600pt, height: 600pt, chart-height: 10),
+)
+
+// =============================================================================
+// TEXT TRUNCATION UTILITIES
+// =============================================================================
+// These functions handle text overflow by adding ellipsis when content
+// exceeds specified dimensions
+
+// Truncates text to fit within a maximum height
+// @param text: The text content to truncate
+// @param maxHeight: Maximum height constraint (optional, defaults to single line height)
+#let truncate_to_height(text, maxHeight: none) = {
+    layout(size => {
+        let text = text
+
+        let maxHeight = if maxHeight != none {
+            maxHeight
+        } else {
+            measure(text).height
+        }
+
+        if measure(width: size.width, text).height <= maxHeight {
+            return text
+        } else {
+            while measure(width: size.width, text + "…").height > maxHeight {
+                // Use character-based slicing instead of byte-based to handle Unicode correctly
+                let chars = text.clusters()
+                if chars.len() == 0 {
+                    break
+                }
+                text = chars.slice(0, chars.len() - 1).join().trim()
+            }
+            return text + "…"
+        }
+    })
+}
+
+// Truncates text to fit within a maximum width
+// @param text: The text content to truncate
+// @param maxWidth: Maximum width constraint (optional, defaults to container width)
+#let truncate_to_width(text, maxWidth: none) = {
+    layout(size => {
+        let text = text
+
+        let maxWidth = if maxWidth != none {
+            maxWidth
+        } else {
+            size.width
+        }
+
+        if measure(text).width <= maxWidth {
+            return text
+        } else {
+            while measure(text + "…").width > maxWidth {
+                // Use character-based slicing instead of byte-based to handle Unicode correctly
+                let chars = text.clusters()
+                if chars.len() == 0 {
+                    break
+                }
+                text = chars.slice(0, chars.len() - 1).join().trim()
+            }
+            return text + "…"
+        }
+    })
+}
+
+// =============================================================================
+// IMAGE UTILITIES
+// =============================================================================
+// Functions for loading and processing images
+
+// Loads an SVG icon and replaces currentColor with the specified color
+// @param icon-name: The name of the SVG file (without .svg extension)
+// @param color: The color to replace currentColor with
+// @param width: The width of the image (default: auto)
+// @param height: The height of the image (default: auto)
+#let colored-image(path, color, width: auto, height: auto) = {
+    let svg = read(path).replace("currentColor", color.to-hex())
+    image(bytes(svg), width: width, height: height)
+}
+
+// =============================================================================
+// AVATAR RENDERING
+// =============================================================================
+// Functions for rendering circular avatar images
+
+// Renders a circular avatar image with border
+// @param avatar-path: Path to the avatar image file
+// @param size: Size of the avatar (default: 1em)
+#let render-avatar(avatar-path, size: 1em, radius: 50%) = {
+    box(clip: true, fill: colors.avatar-bg, stroke: 0.5pt + colors.avatar-border,
+        radius: radius, inset: 1pt,
+        box(clip: true, radius: 50%, image(avatar-path, width: size))
+    )
+}
+
+// =============================================================================
+// AUTHOR HANDLING
+// =============================================================================
+// Complex logic for displaying multiple authors with proper grammar
+
+// Renders an author with optional avatar and name
+#let render-author(author) = {
+    if author.avatar != none {
+        h(0.2em)
+        box(baseline: 30%, [#render-avatar(author.avatar, size: 1.25em)])
+        h(0.2em)
+    }
+    [*\@#author.name*]
+}
+
+// Renders a community with optional avatar and name
+#let render-community(community) = {
+    if community.avatar != none {
+        h(0.2em)
+        box(baseline: 30%, [#render-avatar(community.avatar, size: 1.25em, radius: 25%)])
+        h(0.2em)
+    }
+    [*i\/#community.handle*]
+}
+
+#let render-author-community(author, community) = {
+  [By #render-author(author) in #render-community(community)]
+}
+
+// =============================================================================
+// VISUAL COMPONENTS
+// =============================================================================
+// Reusable components for consistent styling
+
+// Renders the header with the logo, on top of the community banner if available
+// @param banner: Path to the banner image file (default: none)
+#let render-header(banner: none) = {
+    if banner != none {
+        place(top + left, image(banner, width: 100%, height: header-height, fit: "cover"))
+        // Fade the banner into the background so the logo stays readable
+        let fade = rgb(theme.background.at(0))
+        place(top + left, rect(width: 100%, height: header-height, stroke: none,
+            fill: gradient.linear(fade, fade.transparentize(40%), fade.transparentize(100%))))
+    }
+    rect(width: 100%, height: header-height, stroke: none, {
+        place(left + horizon, dx: 30pt, {
+            box(baseline: 30%, image(theme.logo, width: 180pt))
+            // h(10pt)
+            // text(size: 22pt, fill: colors.header-text, weight: "semibold")[inetia.social]
+        })
+    })
+}
+
+// Renders a tag/keyword with consistent styling
+#let render-tag(content) = {
+    set text(fill: colors.tag-text)
+    box(fill: colors.tag-bg, radius: .15em, inset: (x: .4em, y: .25em),
+        content
+    )
+}
+
+// Renders a metadata item with icon, title, and content
+#let render-metadata(content, icon-name) = {
+    let icon-path = "assets/" + icon-name + ".svg"
+
+    box(inset: (right: 20pt),
+        grid(columns: (auto, auto), rows: (auto, auto), column-gutter: 0.6em, row-gutter: .5em, align: horizon,
+            colored-image(icon-path, colors.text-light, height: 16pt),
+            text([*#content*], size: 16pt, fill: colors.text)
+        )
+    )
+}
+
+// =============================================================================
+// INPUTS
+// =============================================================================
+// Inputs shared by all card kinds. The card data itself is loaded by each
+// template from `data.json`.
+
+#let avatar_map = json("avatar_map.json")
+#let card-layout = layouts.at(sys.inputs.at("layout", default: "opengraph"))
+
+// Returns the path of a downloaded avatar or banner, or none if it was skipped
+// @param url: The URL of the image (may be none)
+#let asset-path(url) = {
+    let path = if url != none { avatar_map.at(url, default: none) }
+    if path != none { "assets/" + path }
+}
+
+// Returns the watermark color, tinted with the community color if available
+// @param community: The community data (may be none)
+#let watermark-color(community) = {
+    let brand-color = if community != none { community.at("color", default: none) }
+    if brand-color != none {
+        rgb(brand-color).transparentize(100% - theme.watermark_opacity * 100%)
+    } else {
+        colors.logo-overlay
+    }
+}
+
+// =============================================================================
+// PAGE
+// =============================================================================
+
+// Sets up the page with background, header and watermark
+// @param community: The community used for the banner and watermark (default: none)
+#let card-page(community: none, body) = {
+    set page(width: card-layout.width, height: card-layout.height, margin: 0pt, fill: colors.bg)
+    set text(font: "IBM Plex Sans", fill: colors.text)
+
+    let banner = if community != none { asset-path(community.at("banner", default: none)) }
+    render-header(banner: banner)
+
+    // Inertia logo overlay (translucent watermark, tinted with the community color)
+    place(bottom + right, dx: 150pt, dy: 90pt,
+        colored-image("assets/inertia.svg", watermark-color(community), width: 420pt)
+    )
+
+    body
+}
@@ -0,0 +1,53 @@
+// =============================================================================
+// Inertia OG image template: community page
+// =============================================================================
+// This template generates Open Graph images for community pages.
+
+// =============================================================================
+// IMPORTS
+// =============================================================================
+
+#import "common.typ": *
+
+// =============================================================================
+// DATA LOADING
+// =============================================================================
+
+#let data = json("data.json")
+
+// =============================================================================
+// MAIN DOCUMENT
+// =============================================================================
+
+#show: card-page.with(community: data.community)
+
+// Main content area
+#place(
+    left + top,
+    dy: 40pt,
+    block(height: 100% - header-height, inset: 35pt, clip: false, {
+        // Avatar, handle and member count
+        let avatar = asset-path(data.community.avatar)
+        block(grid(columns: (auto, 1fr), column-gutter: 16pt, align: horizon,
+            if avatar != none { render-avatar(avatar, size: 56pt, radius: 25%) },
+            stack(dir: ttb, spacing: 8pt,
+                text(size: 28pt, weight: "semibold", fill: colors.primary,
+                    truncate_to_width([i\/#data.community.handle])),
+                text(size: 15pt, fill: colors.text-light, [*#data.members* members]),
+            ),
+        ))
+
+        v(12pt)
+
+        // Top markets
+        set text(size: 15pt)
+        for market in data.top_markets.slice(0, calc.min(3, data.top_markets.len())) {
+            block(below: 10pt, grid(columns: (1fr, auto), column-gutter: 16pt,
+                truncate_to_width(market.question),
+                if market.volume != none {
+                    text(fill: colors.text-light, [*\$#market.volume*])
+                },
+            ))
+        }
+    })
+)
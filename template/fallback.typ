@@ -0,0 +1,27 @@
+// =============================================================================
+// Inertia OG image fallback card
+// =============================================================================
+// Static branded card returned when rendering the regular OG image fails.
+// The pre-rendered result is bundled as `assets/fallback.png`. Regenerate it with:
+//
+//   typst compile --format png --ppi 144 template/fallback.typ template/assets/fallback.png
+//   oxipng --opt 4 --strip safe template/assets/fallback.png
+
+#let colors = (
+    bg: gradient.linear(rgb(0, 97, 63), rgb(0, 51, 33), rgb(0, 25, 17), rgb(0, 25, 17), rgb(0, 25, 17),  rgb(0, 97, 63), angle: 45deg),
+    logo-overlay: oklch(43.5%, 0.1, 161deg, 30%),
+)
+
+#let colored-image(path, color, width: auto, height: auto) = {
+    let svg = read(path).replace("currentColor", color.to-hex())
+    image(bytes(svg), width: width, height: height)
+}
+
+#set page(width: 600pt, height: 315pt, margin: 0pt, fill: colors.bg)
+
+// Inertia logo overlay (30% opacity watermark)
+#place(bottom + right, dx: 150pt, dy: 90pt,
+    colored-image("assets/inertia.svg", colors.logo-overlay, width: 420pt)
+)
+
+#place(center + horizon, image("assets/og-template.svg", width: 320pt))
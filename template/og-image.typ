@@ -3,214 +3,61 @@
 // Based on the crates.io OG image template:
 // https://github.com/rust-lang/crates_io_og_image
 // =============================================================================
-// This template generates Open Graph images for crates.io crate.
+// This template generates Open Graph images for prediction markets.
 
 // =============================================================================
-// COLOR PALETTE
+// IMPORTS
 // =============================================================================
 
 #import "@preview/cetz:0.4.0": canvas, draw
 #import "@preview/cetz-plot:0.1.2": plot, chart
+#import "common.typ": *
 
-#let colors = (
-    bg: gradient.linear(rgb(0, 97, 63), rgb(0, 51, 33), rgb(0, 25, 17), rgb(0, 25, 17), rgb(0, 25, 17),  rgb(0, 97, 63), angle: 45deg),
-    logo-overlay: oklch(43.5%, 0.1, 161deg, 30%),
-    header-text: oklch(100%, 0, 0deg),
-    primary: rgb(41, 255, 180),
-    yes: rgb(0, 242, 156),
-    no: oklch(63.68%, 0.152, 25.2deg),
-    text: rgb(229, 255, 246),
-    text-light: rgb(204, 255, 237),
-    avatar-bg: oklch(100%, 0, 0deg),
-    avatar-border: oklch(87%, 0.01, 98deg),
-)
-
-// =============================================================================
-// LAYOUT CONSTANTS
-// =============================================================================
-
-#let header-height = 80pt
-
-// =============================================================================
-// TEXT TRUNCATION UTILITIES
-// =============================================================================
-// These functions handle text overflow by adding ellipsis when content
-// exceeds specified dimensions
-
-// Truncates text to fit within a maximum height
-// @param text: The text content to truncate
-// @param maxHeight: Maximum height constraint (optional, defaults to single line height)
-#let truncate_to_height(text, maxHeight: none) = {
-    layout(size => {
-        let text = text
-
-        let maxHeight = if maxHeight != none {
-            maxHeight
-        } else {
-            measure(text).height
-        }
-
-        if measure(width: size.width, text).height <= maxHeight {
-            return text
-        } else {
-            while measure(width: size.width, text + "…").height > maxHeight {
-                // Use character-based slicing instead of byte-based to handle Unicode correctly
-                let chars = text.clusters()
-                if chars.len() == 0 {
-                    break
-                }
-                text = chars.slice(0, chars.len() - 1).join().trim()
-            }
-            return text + "…"
-        }
-    })
-}
-
-// Truncates text to fit within a maximum width
-// @param text: The text content to truncate
-// @param maxWidth: Maximum width constraint (optional, defaults to container width)
-#let truncate_to_width(text, maxWidth: none) = {
-    layout(size => {
-        let text = text
-
-        let maxWidth = if maxWidth != none {
-            maxWidth
-        } else {
-            size.width
-        }
-
-        if measure(text).width <= maxWidth {
-            return text
-        } else {
-            while measure(text + "…").width > maxWidth {
-                // Use character-based slicing instead of byte-based to handle Unicode correctly
-                let chars = text.clusters()
-                if chars.len() == 0 {
-                    break
-                }
-                text = chars.slice(0, chars.len() - 1).join().trim()
-            }
-            return text + "…"
-        }
-    })
-}
-
-// =============================================================================
-// IMAGE UTILITIES
-// =============================================================================
-// Functions for loading and processing images
-
-// Loads an SVG icon and replaces currentColor with the specified color
-// @param icon-name: The name of the SVG file (without .svg extension)
-// @param color: The color to replace currentColor with
-// @param width: The width of the image (default: auto)
-// @param height: The height of the image (default: auto)
-#let colored-image(path, color, width: auto, height: auto) = {
-    let svg = read(path).replace("currentColor", color.to-hex())
-    image(bytes(svg), width: width, height: height)
-}
-
-// =============================================================================
-// AVATAR RENDERING
 // =============================================================================
-// Functions for rendering circular avatar images
-
-// Renders a circular avatar image with border
-// @param avatar-path: Path to the avatar image file
-// @param size: Size of the avatar (default: 1em)
-#let render-avatar(avatar-path, size: 1em, radius: 50%) = {
-    box(clip: true, fill: colors.avatar-bg, stroke: 0.5pt + colors.avatar-border,
-        radius: radius, inset: 1pt,
-        box(clip: true, radius: 50%, image(avatar-path, width: size))
-    )
-}
-
-// =============================================================================
-// AUTHOR HANDLING
+// DATA LOADING
 // =============================================================================
-// Complex logic for displaying multiple authors with proper grammar
-
-// Renders an author with optional avatar and name
-#let render-author(author) = {
-    if author.avatar != none {
-        h(0.2em)
-        box(baseline: 30%, [#render-avatar(author.avatar, size: 1.25em)])
-        h(0.2em)
+// Load data from the JSON files written by the generator
+
+#let data = json("data.json")
+
+// Chart options, see `ChartOptions` in the generator
+#let chart-options = json("chart.json")
+#let gridlines = chart-options.at("gridlines", default: ())
+#let time-labels = chart-options.at("time_labels", default: ())
+#let time-axis = chart-options.at("time_axis", default: ())
+#let line-style = chart-options.at("line_style", default: "straight")
+#let markers = chart-options.at("markers", default: "all")
+#let value-axis = chart-options.at("value_axis", default: (min: 0, max: 100))
+#let headline = chart-options.at("headline", default: none)
+
+// Closing or resolution time of the market, like "Ends in 3d"
+#let timing = sys.inputs.at("timing", default: none)
+
+// Kind of market, "probability" or "scalar"
+#let market-kind = data.at("kind", default: (type: "probability")).type
+
+// Formatted current value of a series, like "40%" or "64,250 USD"
+#let format-value(index, value) = {
+    let values = chart-options.at("values", default: ())
+    let formatted = values.at(index, default: none)
+    if formatted != none {
+        formatted
+    } else if market-kind == "probability" {
+        str(value) + "%"
+    } else {
+        str(value)
     }
-    [*\@#author.name*]
 }
 
-// Renders a community with optional avatar and name
-#let render-community(community) = {
-    if community.avatar != none {
-        h(0.2em)
-        box(baseline: 30%, [#render-avatar(community.avatar, size: 1.25em, radius: 25%)])
-        h(0.2em)
-    }
-    [*i\/#community.handle*]
-}
-
-#let render-author-community(author, community) = {
-  [By #render-author(author) in #render-community(community)]
-}
-
-// =============================================================================
-// VISUAL COMPONENTS
-// =============================================================================
-// Reusable components for consistent styling
-
-#let render-header = {
-    rect(width: 100%, height: header-height, stroke: none, {
-        place(left + horizon, dx: 30pt, {
-            box(baseline: 30%, image("assets/og-template.svg", width: 180pt))
-            // h(10pt)
-            // text(size: 22pt, fill: colors.header-text, weight: "semibold")[inetia.social]
-        })
-    })
-}
-
-// Renders a tag/keyword with consistent styling
-#let render-tag(content) = {
-    set text(fill: colors.tag-text)
-    box(fill: colors.tag-bg, radius: .15em, inset: (x: .4em, y: .25em),
-        content
-    )
-}
-
-// Renders a metadata item with icon, title, and content
-#let render-metadata(content, icon-name) = {
-    let icon-path = "assets/" + icon-name + ".svg"
-
-    box(inset: (right: 20pt),
-        grid(columns: (auto, auto), rows: (auto, auto), column-gutter: 0.6em, row-gutter: .5em, align: horizon,
-            colored-image(icon-path, colors.text-light, height: 16pt),
-            text([*#content*], size: 16pt, fill: colors.text)
-        )
-    )
-}
-
-// =============================================================================
-// DATA LOADING
-// =============================================================================
-// Load data from sys.inputs
-
-#let data = json(bytes(sys.inputs.data))
-// #let data = json("data.json")
-#let avatar_map = json(bytes(sys.inputs.at("avatar_map", default: "{}")))
+// Markets with more outcomes show a legend with the outcomes, and only the
+// values at the end of the series, as the full labels would overlap
+#let show-legend = data.graph.len() > 2
 
 // =============================================================================
 // MAIN DOCUMENT
 // =============================================================================
 
-#set page(width: 600pt, height: 315pt, margin: 0pt, fill: colors.bg)
-#set text(font: "IBM Plex Sans", fill: colors.text)
-
-#render-header
-
-// Inertia logo overlay (30% opacity watermark)
-#place(bottom + right, dx: 150pt, dy: 90pt,
-    colored-image("assets/inertia.svg", colors.logo-overlay, width: 420pt)
-)
+#show: card-page.with(community: data.community)
 
 // #place(bottom + center, dx: 0pt, dy: -15pt,
 
@@ -246,25 +93,14 @@
 
         // Author
         set text(size: 15pt, fill: colors.text-light)
-        let author-avatar = none
-        let community-avatar = none
-        let author-with-avatar = {
-            let avatar_path = avatar_map.at(data.author.avatar, default: none)
-            if avatar_path != none {
-                author-avatar = "assets/" + avatar_path
-            }
-            (name: data.author.name, avatar: author-avatar)
-        }
-        let community-with-avatar = {
-            let avatar_path = avatar_map.at(data.author.avatar, default: none)
-            if avatar_path != none {
-                community-avatar = "assets/" + avatar_path
-            }
-            (handle: data.community.handle, avatar: community-avatar)
-        }
+        let author = (name: data.author.name, avatar: asset-path(data.author.avatar))
+        let community = (handle: data.community.handle, avatar: asset-path(data.community.avatar))
+        render-author-community(author, community)
 
-
-        render-author-community(author-with-avatar, community-with-avatar)
+        // Closing or resolution time, like "Ends in 3d"
+        if timing != none {
+          [ · #timing]
+        }
 
         // Metadata
         // stack(dir: ltr,  {
@@ -272,108 +108,167 @@
         //   render-metadata(text([\$#calc.round(data.volume / 100, digits: 2)]), "volume")
         // })
 
-        // Chart
-        canvas(length: 1.2cm, {
-          import draw: *
+        // Current value of scalar markets
+        if headline != none {
+          block(above: 14pt, below: 8pt,
+            text(size: 32pt, weight: "bold", fill: colors.primary, headline))
+        }
 
-          let adjust_timestamps(data_array) = {
-            let sorted_data = data_array.sorted(key: item => item.time)
-            let adjusted = (sorted_data.at(0),)
-            for i in range(1, sorted_data.len()) {
-              let prev_time = adjusted.at(-1).time
-              let current_time = sorted_data.at(i).time
-              let time_diff = current_time - prev_time
-
-              let new_time = if time_diff > 100 {
-                prev_time + 100
-              } else {
-                current_time
+        // Legend, in the order of the outcomes passed in by the generator
+        if show-legend {
+          set text(size: 13pt, fill: colors.text)
+          block(above: 12pt, below: 4pt, {
+            for (i, series) in data.graph.enumerate() {
+              if i > 0 {
+                h(14pt)
               }
-
-              adjusted.push((time: new_time, value: sorted_data.at(i).value))
+              box(baseline: 15%, circle(radius: 5pt, fill: rgb(series.color), stroke: none))
+              h(5pt)
+              [#series.outcome *#format-value(i, series.data.at(-1).value)*]
             }
-            adjusted
-          }
-
-          // Process each graph series and adjust timestamps
-          let adjusted_series = ()
-          let all_times = ()
-          let all_values = ()
-
-          for series in data.graph {
-            let adjusted_data = adjust_timestamps(series.data)
-            adjusted_series.push((
-              outcome: series.outcome,
-              color: series.color,
-              data: adjusted_data
-            ))
-            all_times = all_times + adjusted_data.map(item => item.time)
-            all_values = all_values + adjusted_data.map(item => item.value)
-          }
+          })
+        }
 
-          // Find the maximum end time across all series
-          let max_end_time = calc.max(..all_times)
-
-          // Extend all series to the same end time
-          for i in range(adjusted_series.len()) {
-            let series = adjusted_series.at(i)
-            let last_point = series.data.at(-1)
-            if last_point.time < max_end_time {
-              let extended_data = series.data
-              extended_data.at(-1).time = max_end_time
-              adjusted_series.at(i).data = extended_data
-            }
-          }
+        // Chart, unless the market has no graph data yet
+        if data.graph.len() > 0 { canvas(length: 1.2cm, {
+          import draw: *
 
+          // Positions of the timestamps on the time axis, as computed by the
+          // generator for the configured `TimeScale`. Without chart options,
+          // the real time in hours since the first data point is used.
+          let all_times = data.graph.map(series => series.data.map(item => item.time)).flatten()
           let base_time = calc.min(..all_times)
-          let max_time_hours = calc.max(..all_times.map(t => (t - base_time) / 3600))
-          let max_value = calc.max(..all_values)
+          let time_positions = (:)
+          for (time, x) in time-axis {
+            time_positions.insert(str(time), x)
+          }
+          let to_x(time) = time_positions.at(str(time), default: (time - base_time) / 3600)
 
-          // Convert timestamps to hours and prepare plot data
+          // Convert timestamps to axis positions and prepare plot data
           let plot_series = ()
-          for series in adjusted_series {
-            let plot_data = series.data.map(item => (
-              (item.time - base_time) / 3600,
-              item.value
-            ))
+          for (i, series) in data.graph.enumerate() {
+            let plot_data = series.data
+              .sorted(key: item => item.time)
+              .map(item => (to_x(item.time), item.value))
+            let last_value = plot_data.at(-1).at(1)
             plot_series.push((
               outcome: series.outcome,
               color: series.color,
               data: plot_data,
-              last_value: series.data.at(-1).value
+              last_point: plot_data.at(-1),
+              last_value: last_value,
+              label: format-value(i, last_value),
             ))
           }
 
+          // Extend all series to the same end position, keeping their
+          // current value since the last data point
+          let max_x = calc.max(..plot_series.map(series => series.data.at(-1).at(0)))
+          for i in range(plot_series.len()) {
+            let last_point = plot_series.at(i).last_point
+            if last_point.at(0) < max_x {
+              plot_series.at(i).data.push((max_x, last_point.at(1)))
+            }
+          }
+
           // Sort series by last value (highest first for proper layering)
           plot_series = plot_series.sorted(key: series => -series.last_value)
 
           let body = {}
 
-          // Add each series to the plot
+          // Horizontal gridlines behind the series
+          if gridlines.len() > 0 {
+            body = plot.add-hline(
+              ..gridlines,
+              style: (stroke: (paint: colors.text-light.transparentize(75%), thickness: 1pt, dash: "dashed")),
+            )
+          }
+
+          // Add each series to the plot, as straight lines or as steps that
+          // change value at the time of each data point
           for series in plot_series {
             body = {
               body
               plot.add(
                 series.data,
+                line: if line-style == "step" { "hv" } else { "linear" },
                 style: (stroke: (paint: rgb(series.color), thickness: 8pt)),
                 mark-style: (fill: rgb(series.color), stroke: rgb(series.color)),
+                mark: if markers == "all" { "o" } else { none },
+              )
+            }
+          }
+
+          // Mark the last data point of each series, unless it is at the end
+          // of the chart, where the series ends in a dot anyway
+          if markers == "last" {
+            for series in plot_series.filter(series => series.last_point.at(0) < max_x) {
+              body = {
+                body
+                plot.add(
+                  (series.last_point,),
+                  style: (stroke: none),
+                  mark-style: (fill: rgb(series.color), stroke: rgb(series.color)),
+                  mark: "o",
+                )
+              }
+            }
+          }
+
+          // End each series in a dot, labeled with its current value, and
+          // with its outcome unless the legend is shown
+          for series in plot_series {
+            let last = (max_x, series.last_value)
+            let label = if show-legend {
+              [*#series.label*]
+            } else {
+              [#series.outcome: *#series.label*]
+            }
+            body = {
+              body
+              plot.add(
+                (last,),
+                style: (stroke: none),
+                mark-style: (fill: rgb(series.color), stroke: colors.text),
                 mark: "o",
-                label: text([#series.outcome: *#series.last_value%*], size: 15pt)
+                mark-size: .35,
               )
+              plot.annotate(resize: false, {
+                content(last, anchor: "south-east", padding: .3,
+                  text(label, size: 15pt, fill: rgb(series.color)))
+              })
+            }
+          }
+
+          // Date labels below the time axis, aligned to the edges of the chart
+          for (i, label) in time-labels.enumerate() {
+            let anchor = if i == 0 {
+              "north-west"
+            } else if i == time-labels.len() - 1 {
+              "north-east"
+            } else {
+              "north"
+            }
+            body = {
+              body
+              plot.annotate(resize: false, {
+                content((label.x, value-axis.min), anchor: anchor, padding: .2,
+                  text(size: 11pt, fill: colors.text-light, label.label))
+              })
             }
           }
 
           plot.plot(
-            size: (13, 3.5),
-            y-min: 0,
-            y-max: 100,
-            x-max: max_time_hours,
+            size: (13, card-layout.chart-height),
+            y-min: value-axis.min,
+            y-max: value-axis.max,
+            x-max: max_x,
             legends: none,
             axis-style: none,
             legend: "north-east",
             legend-style: (fill: none, stroke: none),
             body
           )
-        })
+        }) }
     })
 )
@@ -0,0 +1,59 @@
+// =============================================================================
+// Inertia OG image template: user profile
+// =============================================================================
+// This template generates Open Graph images for user profiles.
+
+// =============================================================================
+// IMPORTS
+// =============================================================================
+
+#import "common.typ": *
+
+// =============================================================================
+// DATA LOADING
+// =============================================================================
+
+#let data = json("data.json")
+
+// =============================================================================
+// COMPONENTS
+// =============================================================================
+
+// Renders a single statistic with its value above the label
+// @param value: The formatted value
+// @param label: The label shown below the value
+#let render-stat(value, label) = {
+    box(inset: (right: 40pt), stack(dir: ttb, spacing: 8pt,
+        text(size: 30pt, weight: "semibold", fill: colors.primary, value),
+        text(size: 14pt, fill: colors.text-light, label),
+    ))
+}
+
+// =============================================================================
+// MAIN DOCUMENT
+// =============================================================================
+
+#show: card-page
+
+// Main content area
+#place(
+    left + top,
+    dy: 40pt,
+    block(height: 100% - header-height, inset: 35pt, clip: false, {
+        // Avatar and name
+        let avatar = asset-path(data.avatar)
+        block(grid(columns: (auto, 1fr), column-gutter: 16pt, align: horizon,
+            if avatar != none { render-avatar(avatar, size: 64pt) },
+            text(size: 30pt, weight: "semibold", truncate_to_width([\@#data.name])),
+        ))
+
+        v(16pt)
+
+        // Statistics
+        render-stat(data.predictions, "predictions")
+        if data.win_rate != none {
+            render-stat([#data.win_rate%], "win rate")
+        }
+        render-stat(data.profit_loss, "profit")
+    })
+)